@@ -0,0 +1,267 @@
+//! The AI gives non-player creatures their behaviour. Each creature carries a Brain holding
+//! its current state; every tick the brain looks at what the creature can perceive, picks
+//! a state, and steers the creature through the Moveable trait.
+//!
+//! Wildlife wanders, forages for food and flees the player. Monsters wander until they
+//! spot the player, then chase and attack. Crew follow the captain around the deck once
+//! recruited, or head for and man their station.
+//! All randomness comes from the RNG passed in, so behaviour is repeatable given a seed.
+
+use constants::IMAGE_SIZE_SCALED;
+use creature::{Creature, CreatureKind, Station};
+use misc::*;
+use pathfinding::{Node, Search};
+use rand::Rng;
+
+const WANDER_CHANCE: f64 = 1.0 / 60.0; // Chance per tick a wandering creature changes its mind.
+const FLEE_RADIUS: f64 = 4.0 * IMAGE_SIZE_SCALED; // Wildlife bolts when the player is this close...
+const CALM_RADIUS: f64 = 7.0 * IMAGE_SIZE_SCALED; // ...and settles down again beyond this.
+pub const FORAGE_RADIUS: f64 = 5.0 * IMAGE_SIZE_SCALED;
+const SIGHT_RADIUS: f64 = 6.0 * IMAGE_SIZE_SCALED; // Monsters notice the player within this...
+const GIVE_UP_RADIUS: f64 = 10.0 * IMAGE_SIZE_SCALED; // ...and lose track beyond this.
+const ATTACK_RADIUS: f64 = IMAGE_SIZE_SCALED;
+const ATTACK_COOLDOWN: i32 = 60; // Ticks between attacks.
+const FOLLOW_RADIUS: f64 = 2.0 * IMAGE_SIZE_SCALED; // Crew keep about this close to the captain.
+const DEAD_ZONE: f64 = IMAGE_SIZE_SCALED / 4.0; // Closer than this on an axis counts as level.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AiState {
+    Wander,
+    Forage,
+    Flee,
+    Chase,
+    Attack,
+    Follow,
+    Travel,
+    Work,
+}
+
+/**
+    What a creature can sense this tick. Positions are in world space.
+
+    @field player Where the player is, if on the same ground (island or deck) as the creature.
+    @field food Where the nearest food item is, if any is close by.
+    @field waypoint The next step on the creature's route, if it has one.
+*/
+pub struct Perception {
+    pub player: Option<(f64, f64)>,
+    pub food: Option<(f64, f64)>,
+    pub waypoint: Option<(f64, f64)>,
+}
+
+/**
+    Implementation of the Brain object.
+
+    @field state What the creature is currently doing.
+    @field blocked Whether the creature's last move was blocked by terrain.
+    @field cooldown Ticks until the creature can attack again.
+    @field route The steps left on the creature's current route.
+    @field search The latest search for a route, possibly still under way.
+*/
+pub struct Brain {
+    pub state: AiState,
+    pub blocked: bool,
+    cooldown: i32,
+    pub route: Vec<Node>,
+    pub search: Option<Search>,
+}
+
+impl Brain {
+    /*
+        Brain constructor. Every creature starts out wandering.
+
+        @return Brain Returns itself.
+    */
+    pub fn new() -> Self {
+        Brain {
+            state: AiState::Wander,
+            blocked: false,
+            cooldown: 0,
+            route: vec![],
+            search: None,
+        }
+    }
+}
+
+/*
+    Runs a creature's brain for one tick: picks its state from what it perceives,
+    then sets its directions and speed to match.
+
+    @param creature The creature thinking.
+    @param perception What the creature can sense.
+    @param rng The random number generator driving any choices.
+    @return bool Whether the creature attacks the player this tick.
+*/
+pub fn think<R: Rng>(creature: &mut Creature, perception: &Perception, rng: &mut R) -> bool {
+    let here = (creature.x, creature.y);
+    let player_distance = perception.player.map(|player| distance(here, player));
+    let state = next_state(creature, player_distance, perception);
+    if state != creature.brain.state {
+        // Whatever the creature was doing, it drops it.
+        creature.directions = vec![];
+    }
+    creature.brain.state = state;
+    if creature.brain.cooldown > 0 {
+        creature.brain.cooldown -= 1;
+    }
+
+    let base_speed = creature.kind.base_speed();
+    let mut attacks = false;
+    match state {
+        AiState::Wander => {
+            creature.speed = base_speed;
+            // Bumping into something is a good reason to try another way.
+            if creature.brain.blocked || rng.gen::<f64>() < WANDER_CHANCE {
+                creature.directions = random_directions(rng);
+            }
+        }
+        AiState::Forage => {
+            creature.speed = base_speed;
+            creature.directions = directions_toward(here, perception.food.unwrap_or(here));
+        }
+        AiState::Flee => {
+            creature.speed = base_speed * 1.8;
+            let player = perception.player.unwrap_or(here);
+            creature.directions = directions_toward(player, here);
+            if creature.brain.blocked {
+                // Cornered: scramble sideways.
+                creature.directions = random_directions(rng);
+            }
+        }
+        AiState::Chase => {
+            creature.speed = base_speed * 1.3;
+            creature.directions = match perception.waypoint {
+                // Line up closely with each step, so as not to clip corners.
+                Some(waypoint) => directions_within(here, waypoint, creature.speed),
+                None => directions_toward(here, perception.player.unwrap_or(here)),
+            };
+        }
+        AiState::Travel => {
+            creature.speed = base_speed;
+            let waypoint = perception.waypoint.unwrap_or(here);
+            creature.directions = directions_within(here, waypoint, creature.speed);
+        }
+        AiState::Work => creature.directions = vec![],
+        AiState::Attack => {
+            creature.directions = vec![];
+            if creature.brain.cooldown == 0 {
+                creature.brain.cooldown = ATTACK_COOLDOWN;
+                attacks = true;
+            }
+        }
+        AiState::Follow => {
+            creature.speed = base_speed;
+            creature.directions = match player_distance {
+                Some(d) if d > FOLLOW_RADIUS => {
+                    directions_toward(here, perception.player.unwrap_or(here))
+                }
+                _ => vec![],
+            };
+        }
+    }
+    creature.update_self_velocity();
+    attacks
+}
+
+/*
+    Chooses a creature's next state, based on its kind and what it perceives.
+*/
+fn next_state(
+    creature: &Creature,
+    player_distance: Option<f64>,
+    perception: &Perception,
+) -> AiState {
+    // Distances with no player around compare as infinitely far.
+    let player_distance = player_distance.unwrap_or(f64::INFINITY);
+    let state = creature.brain.state;
+    match creature.kind {
+        CreatureKind::Wildlife => {
+            let scared = player_distance < FLEE_RADIUS
+                || (state == AiState::Flee && player_distance < CALM_RADIUS);
+            if scared {
+                AiState::Flee
+            } else if perception.food.is_some() {
+                AiState::Forage
+            } else {
+                AiState::Wander
+            }
+        }
+        CreatureKind::Monster => {
+            if player_distance < ATTACK_RADIUS {
+                AiState::Attack
+            } else if player_distance < SIGHT_RADIUS
+                || (state != AiState::Wander && player_distance < GIVE_UP_RADIUS)
+            {
+                AiState::Chase
+            } else {
+                AiState::Wander
+            }
+        }
+        CreatureKind::Crew => {
+            if perception.waypoint.is_some() {
+                AiState::Travel
+            } else if !creature.recruited {
+                AiState::Wander
+            } else {
+                match creature.station {
+                    Some(Station::Helm) | Some(Station::Repair) => AiState::Work,
+                    Some(_) => AiState::Wander,
+                    None if player_distance.is_finite() => AiState::Follow,
+                    None => AiState::Wander,
+                }
+            }
+        }
+        CreatureKind::Player => state,
+    }
+}
+
+/*
+    Finds the directions leading from one point to another, diagonals included.
+
+    @param from Where the creature is.
+    @param to Where it wants to go.
+    @return Vec<Direction> The directions to move in; none if already there.
+*/
+pub fn directions_toward(from: (f64, f64), to: (f64, f64)) -> Vec<Direction> {
+    directions_within(from, to, DEAD_ZONE)
+}
+
+/*
+    Finds the directions leading from one point to another, ignoring any axis
+    already within a tolerance.
+*/
+fn directions_within(from: (f64, f64), to: (f64, f64), tolerance: f64) -> Vec<Direction> {
+    let mut directions = vec![];
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    if dx > tolerance {
+        directions.push(Direction::E);
+    } else if dx < -tolerance {
+        directions.push(Direction::W);
+    }
+    if dy > tolerance {
+        directions.push(Direction::S);
+    } else if dy < -tolerance {
+        directions.push(Direction::N);
+    }
+    directions
+}
+
+/*
+    Picks a random direction to amble in, or none to stand still for a while.
+*/
+fn random_directions<R: Rng>(rng: &mut R) -> Vec<Direction> {
+    match rng.gen_range(0, 6) {
+        0 => vec![Direction::N],
+        1 => vec![Direction::E],
+        2 => vec![Direction::S],
+        3 => vec![Direction::W],
+        _ => vec![],
+    }
+}
+
+/*
+    Straight-line distance between two points.
+*/
+fn distance(a: (f64, f64), b: (f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+}
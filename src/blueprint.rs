@@ -0,0 +1,258 @@
+//! Ship blueprints are human-editable text files describing a ship's layout and handling,
+//! so ship variants can be authored without recompiling.
+//!
+//! A blueprint is a list of directives, one per line, followed by the layout itself:
+//!
+//! ```text
+//! # Comments and blank lines are ignored.
+//! name Default Airship
+//! drag 0.04
+//! legend . Air
+//! legend + WoodFloor
+//! layout
+//! .+.
+//! +++
+//! ```
+//!
+//! Each `legend` line maps a single character to a tile type. Every line after `layout`
+//! is a row of the ship, one character per tile, and all rows must be the same width.
+
+use misc::DataError;
+use ship::Ship;
+use std::fs;
+use std::path::Path;
+use tile::{Tile, TileType};
+
+pub const DEFAULT_BLUEPRINT: &str = include_str!("../assets/ships/default.ship");
+pub const EXPORT_FILE: &str = "exported.ship";
+const KIND: &str = "blueprint"; // Names blueprints in errors.
+
+// Characters used when exporting, for each tile type a ship may hold.
+const EXPORT_LEGEND: [(char, TileType); 10] = [
+    ('.', TileType::Air),
+    ('+', TileType::WoodFloor),
+    ('W', TileType::Wheel),
+    ('P', TileType::Portal),
+    ('S', TileType::Sail),
+    ('*', TileType::Propeller),
+    ('O', TileType::Balloon),
+    ('C', TileType::CargoHold),
+    ('B', TileType::Workbench),
+    ('R', TileType::RepairPost),
+];
+
+/**
+    Implementation of the Blueprint object.
+
+    @field name The ship's name.
+    @field rows The ship's tiles, row by row as written.
+    @field drag Optional override of the ship's drag.
+    @field turn_response Optional override of the ship's turn response.
+*/
+#[derive(Debug)]
+pub struct Blueprint {
+    pub name: String,
+    pub rows: Vec<Vec<TileType>>,
+    pub drag: Option<f64>,
+    pub turn_response: Option<f64>,
+}
+
+impl Blueprint {
+    /*
+        Parses a blueprint from text.
+
+        @param text The blueprint's contents.
+        @return Result<Blueprint, DataError> The blueprint, or where parsing failed.
+    */
+    pub fn parse(text: &str) -> Result<Blueprint, DataError> {
+        let mut blueprint = Blueprint {
+            name: String::new(),
+            rows: vec![],
+            drag: None,
+            turn_response: None,
+        };
+        let mut legend: Vec<(char, TileType)> = vec![];
+        let mut in_layout = false;
+        let mut last_line = 0;
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            last_line = line_number;
+            if in_layout {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let mut row = vec![];
+                for (col, c) in line.trim_end().chars().enumerate() {
+                    match legend.iter().find(|&&(l, _)| l == c) {
+                        Some((_, tile_type)) => row.push(tile_type.clone()),
+                        None => {
+                            let message = format!("'{}' is not in the legend", c);
+                            let column = col + 1;
+                            return Err(DataError::parse_at(KIND, line_number, column, &message));
+                        }
+                    }
+                }
+                if let Some(first) = blueprint.rows.first() {
+                    if row.len() != first.len() {
+                        let message =
+                            format!("row is {} wide, expected {}", row.len(), first.len());
+                        let column = row.len().min(first.len()) + 1;
+                        return Err(DataError::parse_at(KIND, line_number, column, &message));
+                    }
+                }
+                blueprint.rows.push(row);
+                continue;
+            }
+
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let indent = line.len() - trimmed.len();
+            let (keyword, rest) = match trimmed.find(char::is_whitespace) {
+                Some(end) => (&trimmed[..end], trimmed[end..].trim_start()),
+                None => (trimmed, ""),
+            };
+            // Column where the directive's value begins.
+            let value_column = indent + trimmed.len() - rest.len() + 1;
+            let number = || parse_number(rest, line_number, value_column);
+
+            match keyword {
+                "name" => {
+                    if rest.is_empty() {
+                        let message = "missing ship name";
+                        return Err(DataError::parse_at(KIND, line_number, value_column, message));
+                    }
+                    blueprint.name = rest.trim_end().to_string();
+                }
+                "drag" => blueprint.drag = Some(number()?),
+                "turn_response" => blueprint.turn_response = Some(number()?),
+                "legend" => {
+                    let mut chars = rest.chars();
+                    let c = match chars.next() {
+                        Some(c) => c,
+                        None => {
+                            let message = "missing legend character";
+                            let column = value_column;
+                            return Err(DataError::parse_at(KIND, line_number, column, message));
+                        }
+                    };
+                    let name = chars.as_str().trim();
+                    let name_column = value_column + rest.len() - chars.as_str().trim_start().len();
+                    if legend.iter().any(|&(l, _)| l == c) {
+                        let message = format!("'{}' is already in the legend", c);
+                        let column = value_column;
+                        return Err(DataError::parse_at(KIND, line_number, column, &message));
+                    }
+                    match TileType::from_name(name) {
+                        Some(tile_type) => legend.push((c, tile_type)),
+                        None => {
+                            let message = format!("unknown tile type '{}'", name);
+                            let column = name_column;
+                            return Err(DataError::parse_at(KIND, line_number, column, &message));
+                        }
+                    }
+                }
+                "layout" => in_layout = true,
+                _ => {
+                    let message = format!("unknown directive '{}'", keyword);
+                    return Err(DataError::parse_at(KIND, line_number, indent + 1, &message));
+                }
+            }
+        }
+
+        if blueprint.rows.is_empty() {
+            let message = "blueprint has no layout";
+            return Err(DataError::parse_at(KIND, last_line.max(1), 1, message));
+        }
+        Ok(blueprint)
+    }
+
+    /*
+        Reads and parses a blueprint file.
+
+        @param path The file's path.
+        @return Result<Blueprint, DataError> The blueprint, or why it could not be loaded.
+    */
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Blueprint, DataError> {
+        let text = fs::read_to_string(path)?;
+        Blueprint::parse(&text)
+    }
+
+    /*
+        Builds the ship this blueprint describes.
+
+        @return Ship The new ship, at the origin.
+    */
+    pub fn build(&self) -> Ship {
+        let width = self.rows[0].len();
+        let tiles = (0..width)
+            .map(|i| {
+                self.rows
+                    .iter()
+                    .map(|row| Tile::new(row[i].clone()))
+                    .collect()
+            })
+            .collect();
+        let mut ship = Ship::from_tiles(tiles);
+        if let Some(drag) = self.drag {
+            ship.drag = drag;
+        }
+        if let Some(turn_response) = self.turn_response {
+            ship.turn_response = turn_response;
+        }
+        ship
+    }
+}
+
+/*
+    Writes a ship out as a blueprint, including its handling. Tile health is not kept.
+
+    @param ship The ship to export.
+    @param name The name to give the blueprint.
+    @return Result<String, DataError> The blueprint's text, or where a tile had no symbol.
+*/
+pub fn export(ship: &Ship, name: &str) -> Result<String, DataError> {
+    let mut out = String::new();
+    out.push_str(&format!("name {}\n", name));
+    out.push_str(&format!("drag {}\n", ship.drag));
+    out.push_str(&format!("turn_response {}\n", ship.turn_response));
+
+    // Only list the tile types the ship uses.
+    for &(c, ref tile_type) in EXPORT_LEGEND.iter() {
+        if ship.has_tile(tile_type.clone()) {
+            out.push_str(&format!("legend {} {:?}\n", c, tile_type));
+        }
+    }
+
+    out.push_str("layout\n");
+    for j in 0..ship.height as usize {
+        let line = out.lines().count() + 1;
+        for (i, column) in ship.tiles.iter().enumerate() {
+            let tile_type = &column[j].tile_type;
+            match EXPORT_LEGEND.iter().find(|&(_, t)| t == tile_type) {
+                Some(&(c, _)) => out.push(c),
+                None => {
+                    let message = format!("no symbol for {:?}", tile_type);
+                    return Err(DataError::parse_at(KIND, line, i + 1, &message));
+                }
+            }
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/*
+    Parses a directive's numeric value.
+*/
+fn parse_number(value: &str, line: usize, column: usize) -> Result<f64, DataError> {
+    value
+        .trim_end()
+        .parse()
+        .map_err(|_| {
+            let message = format!("'{}' is not a number", value.trim_end());
+            DataError::parse_at(KIND, line, column, &message)
+        })
+}
@@ -4,26 +4,36 @@
 pub const IMAGE_SIZE: f64 = 8.0;
 pub const IMAGE_SCALE: f64 = 4.0;
 pub const IMAGE_SIZE_SCALED: f64 = IMAGE_SCALE * IMAGE_SIZE;
-pub const MAP_WIDTH: usize = 1000;
-pub const MAP_HEIGHT: usize = 1000;
+pub const CHUNK_SIZE: i32 = 32;
+pub const CHUNK_LOAD_RADIUS: i32 = 2;
+pub const CHUNK_UNLOAD_RADIUS: i32 = 3;
 
 pub const IMG_SKY: &str = "sky";
 pub const IMG_CLOUD_1: &str = "cloud_1";
 pub const IMG_CLOUD_2: &str = "cloud_2";
 
 pub const IMG_WOOD_FLOOR: &str = "wood_floor";
+pub const IMG_WOOD_WALL_POST: &str = "wood_wall_post";
+pub const IMG_WOOD_WALL_END: &str = "wood_wall_end";
+pub const IMG_WOOD_WALL_STRAIGHT: &str = "wood_wall_straight";
+pub const IMG_WOOD_WALL_CORNER: &str = "wood_wall_corner";
+pub const IMG_WOOD_WALL_TEE: &str = "wood_wall_tee";
+pub const IMG_WOOD_WALL_CROSS: &str = "wood_wall_cross";
+pub const IMG_DOOR: &str = "door";
+pub const IMG_CHEST: &str = "chest";
 
 pub const IMG_WHEEL: &str = "wheel";
 pub const IMG_TREE: &str = "tree";
 pub const IMG_GRUNE: &str = "grune";
 pub const IMG_PORTAL: &str = "portal";
+pub const IMG_SAIL: &str = "sail";
+pub const IMG_PROPELLER: &str = "propeller";
+pub const IMG_BALLOON: &str = "balloon";
+pub const IMG_CARGO_HOLD: &str = "cargo_hold";
+pub const IMG_WORKBENCH: &str = "workbench";
+pub const IMG_REPAIR_POST: &str = "repair_post";
 pub const IMG_HEART: &str = "heart";
 
-pub const IMG_ITEM_BISKET: &str = "bisket_item";
-pub const IMG_ITEM_SWORD: &str = "sword_item";
-pub const IMG_ITEM_GRUNE: &str = "grune_item";
-pub const IMG_ITEM_LOGS: &str = "logs_item";
-
 pub const IMG_STONE_WALL: &str = "stone_wall_1";
 pub const IMG_STONE_WALL_TEXTURE: &str = "stone_wall_2";
 
@@ -85,3 +95,11 @@ pub const IMG_PLAYER_MOVING_E_2: &str = "player_moving_E_3";
 pub const IMG_PLAYER_MOVING_W_0: &str = "player_moving_W_1";
 pub const IMG_PLAYER_MOVING_W_1: &str = "player_moving_W_2";
 pub const IMG_PLAYER_MOVING_W_2: &str = "player_moving_W_3";
+
+pub const IMG_WILDLIFE_1: &str = "wildlife_1";
+pub const IMG_WILDLIFE_2: &str = "wildlife_2";
+pub const IMG_WILDLIFE_3: &str = "wildlife_3";
+
+pub const IMG_MONSTER_1: &str = "monster_1";
+pub const IMG_MONSTER_2: &str = "monster_2";
+pub const IMG_MONSTER_3: &str = "monster_3";
@@ -0,0 +1,160 @@
+//! Crafting turns stacks of items into others. Recipes are read from a text file
+//! in the data folder.
+//!
+//! A recipe file is a list of directives, one per line. Each recipe begins with its
+//! name, followed by what it takes, what it makes, and optionally where it is made:
+//!
+//! ```text
+//! # Comments and blank lines are ignored.
+//! recipe Planks
+//! station Workbench
+//! input logs 1
+//! output planks 4
+//! ```
+//!
+//! A recipe may list any number of inputs and outputs, by item prototype name.
+
+use item::Item;
+use misc::DataError;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tile::TileType;
+
+pub const DEFAULT_RECIPES: &str = include_str!("../assets/data/recipes.txt");
+const KIND: &str = "recipes"; // Names recipe files in errors.
+
+/**
+    Implementation of the Recipe object.
+
+    @field name The recipe's name.
+    @field inputs The items used up, as prototype names and counts.
+    @field outputs The items made, as prototype names and counts.
+    @field station The tile the crafter must stand beside, if any.
+*/
+#[derive(Clone, Debug)]
+pub struct Recipe {
+    pub name: String,
+    pub inputs: Vec<(String, u32)>,
+    pub outputs: Vec<(String, u32)>,
+    pub station: Option<TileType>,
+}
+
+/**
+    Implementation of the RecipeBook object: every recipe known, in the order written.
+
+    @field recipes The recipes.
+*/
+#[derive(Clone, Debug)]
+pub struct RecipeBook {
+    pub recipes: Vec<Recipe>,
+}
+
+impl RecipeBook {
+    /*
+        Parses recipes from text. Every item named must have a prototype.
+
+        @param text The recipe file's contents.
+        @param items The item prototypes, by name.
+        @return Result<RecipeBook, DataError> The recipes, or where parsing failed.
+    */
+    pub fn parse(text: &str, items: &HashMap<String, Item>) -> Result<RecipeBook, DataError> {
+        let mut recipes: Vec<Recipe> = vec![];
+        let mut starts = vec![]; // The line each recipe begins on.
+
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let keyword = fields.next().unwrap_or("");
+            let rest: Vec<&str> = fields.collect();
+
+            if keyword == "recipe" {
+                if rest.is_empty() {
+                    return Err(DataError::parse(KIND, line_number, "missing recipe name"));
+                }
+                starts.push(line_number);
+                recipes.push(Recipe {
+                    name: rest.join(" "),
+                    inputs: vec![],
+                    outputs: vec![],
+                    station: None,
+                });
+                continue;
+            }
+            let recipe = match recipes.last_mut() {
+                Some(recipe) => recipe,
+                None => {
+                    let message = format!("'{}' before any recipe", keyword);
+                    return Err(DataError::parse(KIND, line_number, &message));
+                }
+            };
+            match keyword {
+                "station" => {
+                    let name = rest.join(" ");
+                    match TileType::from_name(&name) {
+                        Some(tile_type) => recipe.station = Some(tile_type),
+                        None => {
+                            let message = format!("unknown tile type '{}'", name);
+                            return Err(DataError::parse(KIND, line_number, &message));
+                        }
+                    }
+                }
+                "input" => recipe.inputs.push(parse_stack(&rest, items, line_number)?),
+                "output" => recipe.outputs.push(parse_stack(&rest, items, line_number)?),
+                _ => {
+                    let message = format!("unknown directive '{}'", keyword);
+                    return Err(DataError::parse(KIND, line_number, &message));
+                }
+            }
+        }
+
+        // Every recipe must make something from something.
+        for (recipe, &start) in recipes.iter().zip(&starts) {
+            if recipe.inputs.is_empty() || recipe.outputs.is_empty() {
+                let message = format!("recipe '{}' needs inputs and outputs", recipe.name);
+                return Err(DataError::parse(KIND, start, &message));
+            }
+        }
+        Ok(RecipeBook { recipes })
+    }
+
+    /*
+        Reads and parses a recipe file.
+
+        @param path The file's path.
+        @param items The item prototypes, by name.
+        @return Result<RecipeBook, DataError> The recipes, or why they could not be loaded.
+    */
+    pub fn from_file<P: AsRef<Path>>(
+        path: P,
+        items: &HashMap<String, Item>,
+    ) -> Result<RecipeBook, DataError> {
+        let text = fs::read_to_string(path)?;
+        RecipeBook::parse(&text, items)
+    }
+}
+
+/*
+    Parses an item stack: a prototype name and a count.
+*/
+fn parse_stack(
+    fields: &[&str],
+    items: &HashMap<String, Item>,
+    line: usize,
+) -> Result<(String, u32), DataError> {
+    let (id, count) = match *fields {
+        [id, count] => (id, count),
+        _ => return Err(DataError::parse(KIND, line, "expected an item and a count")),
+    };
+    if !items.contains_key(id) {
+        return Err(DataError::parse(KIND, line, &format!("unknown item '{}'", id)));
+    }
+    match count.parse() {
+        Ok(count) if count > 0 => Ok((id.to_string(), count)),
+        _ => Err(DataError::parse(KIND, line, &format!("'{}' is not a count", count))),
+    }
+}
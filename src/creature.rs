@@ -1,24 +1,173 @@
-//! The Creature object is the template for any NPC in AOE. Currently this is used for
-//! the Player, but default functionality will eventually apply for Monsters and Crew.
+//! The Creature object is the template for every character in AOE - the Player, as well
+//! as the Crew, Wildlife and Monsters roaming the world, told apart by their CreatureKind.
 //!
 //! The object handles Creature generation, position and movement, item interaction,
-//! and graphics rendering.
+//! survival needs and graphics rendering.
+//!
+//! The Player must eat to keep going. Food drains over time, faster while walking
+//! and faster still while sprinting; an empty stomach stops stamina recovering and
+//! slowly starves the Player. Stamina is spent sprinting and swinging.
 
+use ai::Brain;
 use constants::*;
+use inventory::Inventory;
 use item::*;
 use misc::*;
 use piston_window::*;
 use std::collections::HashMap;
 
+const SWORD_COOLDOWN: i32 = 30; // Ticks between sword swings.
+const SWING_TICKS: i32 = 8; // How long a swing is shown for.
+const INVULNERABLE_TICKS: i32 = 45; // Grace period after being hit.
+pub const INVENTORY_SLOTS: usize = 8; // Also the length of the hotbar.
+const CARRY_CAPACITY: f64 = 40.0; // The most weight a creature can carry.
+pub const MAX_FOOD: f64 = 100.0;
+pub const MAX_STAMINA: f64 = 100.0;
+const FOOD_DRAIN: f64 = 0.005; // Food lost per tick at rest: a full stomach lasts over 5 minutes.
+const WALK_DRAIN: f64 = 0.005; // Extra food lost per tick while walking, doubled sprinting.
+const EFFORT_DRAIN: f64 = 0.05; // Food lost per point of stamina spent.
+const SPRINT_FACTOR: f64 = 1.6; // Speed multiplier while sprinting.
+const SPRINT_COST: f64 = 0.5; // Stamina spent per tick of sprinting.
+const STAMINA_REGEN: f64 = 0.25; // Stamina recovered per tick when not sprinting.
+const EXHAUSTION_RECOVERY: f64 = 0.25 * MAX_STAMINA; // Stamina needed to sprint again once spent.
+const STARVE_INTERVAL: i32 = 300; // Ticks between each point of damage while starving.
+
 #[derive(Debug, PartialEq)]
 pub enum CreatureState {
     Normal,
     ControllingShip,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CreatureKind {
+    Player,
+    Crew,
+    Wildlife,
+    Monster,
+}
+
+impl CreatureKind {
+    /*
+        Parses a creature kind from its name, as written by Debug.
+
+        @param name The kind's name.
+        @return Option<CreatureKind> The kind, if the name is known.
+    */
+    pub fn from_name(name: &str) -> Option<CreatureKind> {
+        match name {
+            "Player" => Some(CreatureKind::Player),
+            "Crew" => Some(CreatureKind::Crew),
+            "Wildlife" => Some(CreatureKind::Wildlife),
+            "Monster" => Some(CreatureKind::Monster),
+            _ => None,
+        }
+    }
+
+    /*
+        How fast this kind walks at a normal pace.
+
+        @return f64 The kind's base speed.
+    */
+    pub fn base_speed(&self) -> f64 {
+        match *self {
+            CreatureKind::Player => 2.0,
+            CreatureKind::Crew => 1.5,
+            CreatureKind::Wildlife | CreatureKind::Monster => 1.0,
+        }
+    }
+
+    /*
+        How much health this kind starts with.
+
+        @return i32 The kind's starting health.
+    */
+    pub fn max_health(&self) -> i32 {
+        match *self {
+            CreatureKind::Wildlife => 1,
+            CreatureKind::Monster => 4,
+            _ => 3,
+        }
+    }
+
+    /*
+        Determines the texture for a frame of this kind's animation. The Player and
+        Crew share a walk cycle for each direction; animals and monsters have one
+        cycle facing any way.
+
+        @param moving Whether the creature is moving.
+        @param dir The direction the creature faces.
+        @param index The animation frame, from 0 to 2.
+        @return String The texture's name.
+    */
+    pub fn sprite(&self, moving: bool, dir: Direction, index: i32) -> String {
+        let action = if moving { "moving" } else { "idle" };
+        match *self {
+            CreatureKind::Player | CreatureKind::Crew => format!(
+                "player_{}_{}_{}",
+                action,
+                dir.direction_to_string(),
+                index + 1
+            ),
+            CreatureKind::Wildlife => format!("wildlife_{}", index + 1),
+            CreatureKind::Monster => format!("monster_{}", index + 1),
+        }
+    }
+
+    /*
+        The colour this kind's sprite is tinted with, so the Crew stand out from
+        the Player.
+
+        @return [f32; 4] The tint.
+    */
+    pub fn tint(&self) -> [f32; 4] {
+        match *self {
+            CreatureKind::Crew => [0.6, 0.8, 1.0, 1.0],
+            _ => [1.0; 4],
+        }
+    }
+}
+
+// Where a crew member can be put to work.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Station {
+    Helm,
+    Repair,
+    Gather,
+}
+
+// The order stations are cycled through on the roster; None leaves a crew member free.
+const STATION_CYCLE: [Option<Station>; 4] = [
+    None,
+    Some(Station::Helm),
+    Some(Station::Repair),
+    Some(Station::Gather),
+];
+
+impl Station {
+    /*
+        Looks up a station by its name, as written in saves.
+
+        @param name The station's name.
+        @return Option<Station> The station, if the name is known.
+    */
+    pub fn from_name(name: &str) -> Option<Station> {
+        match name {
+            "Helm" => Some(Station::Helm),
+            "Repair" => Some(Station::Repair),
+            "Gather" => Some(Station::Gather),
+            _ => None,
+        }
+    }
+}
+
 /**
 	Implementation of the Creature object.
 
+    @field kind What sort of creature this is.
+    @field on_ship Whether the creature is aboard the ship, rather than in the world.
+    @field brain The creature's AI - unused for the Player.
+    @field recruited Whether the creature has joined the Player's crew.
+    @field station Where a recruited crew member has been put to work, if anywhere.
     @field creature_state Creature's control state - only applicable for Player.
 	@field x Creature's horizontal position on screen.
 	@field y Creature's vertical position on screen.
@@ -29,13 +178,25 @@ pub enum CreatureState {
     @field other_vel_y Horizontal velocity of other object(s) affecting Creature.
     @field speed Creature's maximum speed when moving.
     @field health Creature's health.
-    @field inventory Creature's item inventory.
+    @field food How fed the Creature is, from MAX_FOOD down to 0 when starving.
+    @field stamina Creature's energy for sprinting and swinging, up to MAX_STAMINA.
+    @field sprinting Whether the Creature is trying to sprint.
+    @field inventory Creature's item inventory, with the item in hand selected.
     @field dir Creature's direction for drawing grapics.
     @field sprite_index Used for animating the Creature's sprite.
     @field frames_since_last_draw Used for updating Creature animation.
     @field animation_rate Throttles how often the sprites update.
+    @field attack_cooldown Ticks until the Creature can swing again.
+    @field invulnerable Ticks until the Creature can be hurt again.
+    @field starving Ticks spent starving since the last point of damage.
+    @field exhausted Whether stamina ran out, barring sprinting until it recovers.
 */
 pub struct Creature {
+    pub kind: CreatureKind,
+    pub on_ship: bool,
+    pub brain: Brain,
+    pub recruited: bool,
+    pub station: Option<Station>,
     pub creature_state: CreatureState,
     pub x: f64,
     pub y: f64,
@@ -46,11 +207,18 @@ pub struct Creature {
     pub other_vel_y: f64,
     pub speed: f64,
     pub health: i32,
-    pub inventory: Option<Item>,
+    pub food: f64,
+    pub stamina: f64,
+    pub sprinting: bool,
+    pub inventory: Inventory,
     dir: Direction,
     sprite_index: i32,
     frames_since_last_draw: i32,
     animation_rate: i32,
+    attack_cooldown: i32,
+    invulnerable: i32,
+    starving: i32,
+    exhausted: bool,
 }
 
 impl Creature {
@@ -58,7 +226,22 @@ impl Creature {
         Constructor for default Creature 
     */
     pub fn new() -> Creature {
+        Creature::with_kind(CreatureKind::Player)
+    }
+
+    /*
+        Constructor for a Creature of some kind, with that kind's health and speed.
+
+        @param kind The kind of creature.
+        @return Creature Returns itself.
+    */
+    pub fn with_kind(kind: CreatureKind) -> Creature {
         Creature {
+            kind,
+            on_ship: false,
+            brain: Brain::new(),
+            recruited: false,
+            station: None,
             creature_state: CreatureState::Normal,
             x: 0.0,
             y: 0.0,
@@ -67,13 +250,20 @@ impl Creature {
             directions: vec![],
             other_vel_x: 0.0,
             other_vel_y: 0.0,
-            speed: 2.0,
-            health: 3,
-            inventory: None,
+            speed: kind.base_speed(),
+            health: kind.max_health(),
+            food: MAX_FOOD,
+            stamina: MAX_STAMINA,
+            sprinting: false,
+            inventory: Inventory::new(INVENTORY_SLOTS, CARRY_CAPACITY),
             dir: Direction::S,
             sprite_index: 0,
             frames_since_last_draw: 0,
             animation_rate: 5,
+            attack_cooldown: 0,
+            invulnerable: 0,
+            starving: 0,
+            exhausted: false,
         }
     }
 
@@ -93,11 +283,6 @@ impl Creature {
     pub fn update_position_self(&mut self) {
         self.x += self.self_vel_x;
         self.y += self.self_vel_y;
-        if let Some(ref mut item) = self.inventory {
-            // Update inventory position as well.
-            item.x = self.x;
-            item.y = self.y;
-        }
     }
 
     /*
@@ -139,39 +324,32 @@ impl Creature {
         @param textures The map of sprite / tile textures.
         @param context The context used for drawing, affected by scaling and translation.
         @param graphics Graphics engine.
-        @param w_width Width of the window.
-        @param w_height Height of the window.
+        @param x Where to draw the Creature horizontally on screen.
+        @param y Where to draw the Creature vertically on screen.
     */
     pub fn draw(
         &mut self,
         textures: &HashMap<String, G2dTexture>,
         context: &Context,
         graphics: &mut G2d,
-        w_width: f64,
-        w_height: f64,
+        x: f64,
+        y: f64,
     ) {
-        // Determine the proper file extension based on Creature moving.
-        let pic_index = self.sprite_index + 1;
-        let extension;
-        if self.self_vel_y != 0.0 || self.self_vel_x != 0.0 {
-            extension = "player_moving_";
-        } else {
-            extension = "player_idle_";
+        // Flicker while invulnerable.
+        if self.invulnerable % 8 >= 4 {
+            return;
         }
 
-        let img = &format!(
-            "{}{}{}{}",
-            extension,
-            self.dir.direction_to_string(),
-            "_",
-            pic_index.to_string()
-        );
+        // Determine the proper sprite based on Creature moving.
+        let moving = self.self_vel_y != 0.0 || self.self_vel_x != 0.0;
+        let img = &self.kind.sprite(moving, self.dir, self.sprite_index);
 
-        image(
+        Image::new_color(self.kind.tint()).draw(
             textures.get(img).expect(&format!("Not found: {:?}", img)),
+            &context.draw_state,
             context
                 .transform
-                .trans(w_width / 2.0, w_height / 2.0) // Draw Player at center of screen.
+                .trans(x, y)
                 .scale(IMAGE_SCALE, IMAGE_SCALE),
             graphics,
         );
@@ -183,23 +361,17 @@ impl Creature {
         }
         self.frames_since_last_draw += 1;
 
-        // Display Creature's item(s).
-        if let Some(_) = self.inventory {
-            let item = self.inventory.clone().unwrap();
-            match item.item_type {
-                ItemType::Food(FoodType::Bisket) => {
-                    let img = IMG_ITEM_BISKET;
-                    image(
-                        textures.get(img).expect(&format!("Not found: {:?}", img)),
-                        context
-                            .transform
-                            .trans(w_width / 2.0, w_height / 2.0 - IMAGE_SIZE_SCALED * 0.7) // Draw above Creature.
-                            .scale(IMAGE_SCALE, IMAGE_SCALE),
-                        graphics,
-                    );
-                }
-                _ => {}
-            }
+        // Display the item in the Creature's hand.
+        if let Some(item) = self.inventory.held() {
+            let img = item.image();
+            image(
+                textures.get(img).expect(&format!("Not found: {:?}", img)),
+                context
+                    .transform
+                    .trans(x, y - IMAGE_SIZE_SCALED * 0.7) // Draw above Creature.
+                    .scale(IMAGE_SCALE, IMAGE_SCALE),
+                graphics,
+            );
         }
     }
 
@@ -213,37 +385,134 @@ impl Creature {
     }
 
     /*
-        Deducts from the Creature's health.
+        Deducts from the Creature's health, unless it was hurt too recently.
 
         @param damage The damage to be deducted.
+        @return bool Whether the damage was taken.
     */
-    pub fn take_damage(&mut self, damage: i32) {
+    pub fn take_damage(&mut self, damage: i32) -> bool {
+        if self.invulnerable > 0 {
+            return false;
+        }
         self.health -= damage;
+        self.invulnerable = INVULNERABLE_TICKS;
+        true
     }
 
     /*
-        Removes item from Creature's inventory.
+        Counts down the Creature's attack cooldown and invulnerability by a tick.
+    */
+    pub fn update_timers(&mut self) {
+        self.attack_cooldown = (self.attack_cooldown - 1).max(0);
+        self.invulnerable = (self.invulnerable - 1).max(0);
+    }
 
-        @return Option<Item> The item that is dropped.
+    /*
+        Steps the Creature's hunger and stamina by a tick. Walking works up an
+        appetite, and sprinting more so while stamina lasts. Running out of stamina
+        leaves the Creature exhausted, unable to sprint until a quarter of it is back.
+        Once out of food, stamina no longer recovers and the Creature starts to starve.
+    */
+    pub fn update_needs(&mut self) {
+        if self.stamina <= 0.0 {
+            self.exhausted = true;
+        } else if self.stamina >= EXHAUSTION_RECOVERY {
+            self.exhausted = false;
+        }
+        let moving = !self.directions.is_empty();
+        let sprinting = moving && self.sprinting && !self.exhausted;
+        let mut drain = FOOD_DRAIN;
+        if moving {
+            drain += if sprinting { WALK_DRAIN * 2.0 } else { WALK_DRAIN };
+        }
+        self.food = (self.food - drain).max(0.0);
+
+        if sprinting {
+            self.stamina = (self.stamina - SPRINT_COST).max(0.0);
+        } else if self.food > 0.0 {
+            self.stamina = (self.stamina + STAMINA_REGEN).min(MAX_STAMINA);
+        }
+        let pace = if sprinting { SPRINT_FACTOR } else { 1.0 };
+        self.speed = self.kind.base_speed() * pace;
+        self.update_self_velocity();
+
+        if self.food > 0.0 {
+            self.starving = 0;
+        } else {
+            self.starving += 1;
+            if self.starving >= STARVE_INTERVAL {
+                self.starving = 0;
+                self.health -= 1;
+            }
+        }
+    }
+
+    /*
+        Spends stamina on some effort, which also uses up a little food.
+
+        @param effort The stamina needed.
+        @return bool Whether there was the stamina for it.
+    */
+    pub fn exert(&mut self, effort: f64) -> bool {
+        if self.stamina < effort {
+            return false;
+        }
+        self.stamina -= effort;
+        self.food = (self.food - effort * EFFORT_DRAIN).max(0.0);
+        true
+    }
+
+    /*
+        Starts a swing, if the last one has finished cooling down.
+
+        @return bool Whether the Creature swung.
+    */
+    pub fn start_attack(&mut self) -> bool {
+        if self.attack_cooldown > 0 {
+            return false;
+        }
+        self.attack_cooldown = SWORD_COOLDOWN;
+        true
+    }
+
+    /*
+        Whether a swing is under way, for drawing.
+
+        @return bool Whether the Creature is mid-swing.
+    */
+    pub fn is_swinging(&self) -> bool {
+        self.attack_cooldown > SWORD_COOLDOWN - SWING_TICKS
+    }
+
+    /*
+        The direction the Creature is facing.
+
+        @return Direction The facing direction.
+    */
+    pub fn facing(&self) -> Direction {
+        self.dir
+    }
+
+    /*
+        Removes one of the items in hand from Creature's inventory.
+
+        @return Option<Item> The item that is dropped, placed where the Creature stands.
     */
     pub fn drop_item(&mut self) -> Option<Item> {
-        let dropped_item = self.inventory.clone();
-        self.inventory = None;
-        dropped_item
+        let mut dropped_item = self.inventory.take_held()?;
+        dropped_item.x = self.x;
+        dropped_item.y = self.y;
+        Some(dropped_item)
     }
 
     /*
-        Adds item to Creature's inventory, if empty.
+        Adds item to Creature's inventory, if there is room for it.
 
         @param item The item being picked up.
         @return bool Whether pickup was successful. 
     */
     pub fn pickup_item(&mut self, item: Item) -> bool {
-        if let None = self.inventory {
-            self.inventory = Some(item);
-            return true;
-        }
-        false
+        self.inventory.add(item)
     }
 
     /*
@@ -251,21 +520,43 @@ impl Creature {
     */
     pub fn use_item(&mut self) {
         let mut item_used = false;
-        if let Some(ref item) = self.inventory {
-            match item.item_type {
-                ItemType::Food(_) => {
-                    // Heal Creature.
-                    self.health += 1;
-                    item_used = true;
-                }
-                _ => {}
+        if let Some(item) = self.inventory.held() {
+            if let UseEffect::Eat(food, heal) = item.effect() {
+                // Feed and heal Creature.
+                self.food = (self.food + food).min(MAX_FOOD);
+                self.health += heal;
+                item_used = true;
             }
         }
         if item_used {
-            self.inventory = None; // Empty inventory.
+            self.inventory.take_held(); // Eaten.
         }
     }
 
+    /*
+        Moves a crew member on to the next station in the roster's cycle.
+
+        @param forward Whether to cycle forwards, rather than backwards.
+    */
+    pub fn cycle_station(&mut self, forward: bool) {
+        let count = STATION_CYCLE.len();
+        let index = STATION_CYCLE
+            .iter()
+            .position(|&station| station == self.station)
+            .unwrap_or(0);
+        let next = if forward { index + 1 } else { index + count - 1 };
+        self.station = STATION_CYCLE[next % count];
+    }
+
+    /*
+        Stops the creature's own movement, e.g. when walking into something.
+    */
+    pub fn stop(&mut self) {
+        self.directions = vec![];
+        self.self_vel_x = 0.0;
+        self.self_vel_y = 0.0;
+    }
+
     /*
         Executed for Player when changing between control of ship.
     */
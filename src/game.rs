@@ -3,61 +3,72 @@
 
 use piston_window::*;
 use find_folder::Search;
-use creature::{Creature, CreatureState};
 use texture::TextureSettings;
 use std::collections::HashMap;
-use ship::Ship;
-use misc::*;
-use item::*;
-use map::Map;
+use map::{structure_image, WorldSeed};
+use misc::Direction;
 use constants::*;
-use tile::*;
-use rand::*;
-use rand::distributions::Sample;
+use crafting::{RecipeBook, DEFAULT_RECIPES};
+use creature::{MAX_FOOD, MAX_STAMINA};
+use item::{self, Item};
+use blueprint::{self, Blueprint, DEFAULT_BLUEPRINT, EXPORT_FILE};
+use misc::DataError;
+use ship::Ship;
+use std::fs;
+use save::{self, SAVE_FILE};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use timestep::{lerp, FixedTimestep, MAX_TICKS_PER_UPDATE, TICKS_PER_SECOND};
+use world::{self, Container, PlayerLocation, World};
+
+const NOTICE_DURATION: Duration = Duration::from_secs(4); // How long a notice stays on screen.
 
 #[derive(Debug, PartialEq)]
 pub enum GameState {
     Title,
     InGame,
     InMenu,
+    Crafting,
+    Storage,
     GameOver,
 }
 
-#[derive(Clone)]
-enum PlayerLocation {
-    OnShip,
-    InWorld,
-}
-
 /**
-    Implementation of the Game object.
+    Implementation of the Game object, which renders a World and feeds it input.
 
-    @field player The main player.
-    @field ship The player's airship.
+    @field world The simulated world.
     @field game_state The Game State (see above). 
-    @field player_location Player's worldly position (see above).
-    @field item_prototypes Prototyping pattern for cloning items.
-    @field items_in_game Set of all items in the game.
-    @field map The world map.
     @field glyphs Glyphs library for graphics.
     @field textures HashMap of sprite / tile textures.
-    @field updates_since_last_gen Used for generating new items.
     @field w_width The window width.
     @field w_height The window height.
+    @field can_continue Whether a save file exists to continue from.
+    @field timestep Decides how many simulation ticks to run per update.
+    @field prev_player Player position at the previous tick, for interpolation.
+    @field prev_ship Ship position at the previous tick, for interpolation.
+    @field notice A message for the player, such as a failed save, and when it was given.
+    @field roster_selection Which crew member is selected on the menu's roster.
+    @field recipe_selection Which recipe is selected on the crafting screen.
+    @field storage_column Which side of the storage screen is selected: the player's (0)
+    or the container's (1).
+    @field storage_selection Which slot is selected on the storage screen.
 */
 pub struct Game {
-    player: Creature,
-    ship: Ship,
+    world: World,
     game_state: GameState,
-    player_location: PlayerLocation,
-    item_prototypes: HashMap<String, Item>,
-    items_in_game: Vec<Item>,
-    map: Map,
     glyphs: Glyphs,
     textures: HashMap<String, G2dTexture>,
-    updates_since_last_gen: i32,
     w_width: f64,
     w_height: f64,
+    can_continue: bool,
+    timestep: FixedTimestep,
+    prev_player: (f64, f64),
+    prev_ship: (f64, f64),
+    notice: Option<(String, Instant)>,
+    roster_selection: usize,
+    recipe_selection: usize,
+    storage_column: usize,
+    storage_selection: usize,
 }
 
 impl Game {
@@ -65,35 +76,28 @@ impl Game {
         Game constructor.
     */
     pub fn new(window: &mut PistonWindow) -> Self {
-        let ship_tiles: Vec<Vec<i32>> = vec![
-            // Default ship.
-            vec![0, 0, 1, 3, 1, 0, 0],
-            vec![0, 1, 1, 1, 1, 1, 0],
-            vec![0, 1, 1, 2, 1, 1, 0],
-            vec![1, 1, 1, 1, 1, 1, 1],
-            vec![1, 1, 1, 1, 1, 1, 1],
-            vec![1, 1, 1, 1, 1, 1, 1],
-            vec![1, 1, 1, 1, 1, 1, 1],
-            vec![1, 1, 1, 1, 1, 1, 1],
-        ];
-
-        let item_prototypes = generate_item_prototypes();
         let glyphs = generate_glyphs(window);
-        let textures = generate_textures(window);
+        let mut world = World::with_ship(WorldSeed::random(), load_default_ship());
+        world.item_prototypes = load_items();
+        world.recipes = load_recipes(&world);
+        let textures = generate_textures(window, &world.item_prototypes);
 
         Game {
-            player: Creature::new(),
-            ship: Ship::new(ship_tiles),
+            world,
             game_state: GameState::Title,
-            player_location: PlayerLocation::OnShip,
-            item_prototypes: item_prototypes,
-            items_in_game: vec![],
-            map: Map::new(MAP_WIDTH, MAP_HEIGHT),
             glyphs: glyphs,
             textures: textures,
-            updates_since_last_gen: 0,
             w_width: 800.0,
             w_height: 640.0,
+            can_continue: Path::new(SAVE_FILE).exists(),
+            timestep: FixedTimestep::new(TICKS_PER_SECOND, MAX_TICKS_PER_UPDATE),
+            prev_player: (0.0, 0.0),
+            prev_ship: (0.0, 0.0),
+            notice: None,
+            roster_selection: 0,
+            recipe_selection: 0,
+            storage_column: 0,
+            storage_selection: 0,
         }
     }
 
@@ -111,12 +115,21 @@ impl Game {
             let w_height = window_size.height as f64;
             self.w_width = w_width;
             self.w_height = w_height;
+            self.world.view_width = w_width;
+            self.world.view_height = w_height;
             clear([0.0, 0.0, 0.0, 1.0], graphics); // Clears screen for new draw.
             match self.game_state {
                 GameState::InGame => {
+                    // Positions interpolated between the last two ticks.
+                    let alpha = self.timestep.alpha();
+                    let player_x = lerp(self.prev_player.0, self.world.player.x, alpha);
+                    let player_y = lerp(self.prev_player.1, self.world.player.y, alpha);
+                    let ship_x = lerp(self.prev_ship.0, self.world.ship.x, alpha);
+                    let ship_y = lerp(self.prev_ship.1, self.world.ship.y, alpha);
+
                     // Translations for objects around the player.
-                    let trans_x = w_width / 2.0 - self.player.x;
-                    let trans_y = w_height / 2.0 - self.player.y;
+                    let trans_x = w_width / 2.0 - player_x;
+                    let trans_y = w_height / 2.0 - player_y;
 
                     let sky_img_string = "sky";
                     // Sky background.
@@ -128,53 +141,194 @@ impl Game {
                         graphics,
                     );
 
-                    self.map.draw(
+                    self.world.map.draw(
                         &self.textures,
                         &context,
                         &mut graphics,
                         w_width,
                         w_height,
-                        self.player.x,
-                        self.player.y,
+                        player_x,
+                        player_y,
                         trans_x,
                         trans_y,
                     );
 
-                    // Draw items.
-                    for i in 0..self.items_in_game.len() {
-                        if self.items_in_game[i].x - self.player.x
-                            > -w_width / 2.0 - IMAGE_SIZE_SCALED
-                            && self.items_in_game[i].x - self.player.x < w_width / 2.0
-                            && self.items_in_game[i].y - self.player.y > -w_width / 2.0
-                            && self.items_in_game[i].y - self.player.y < w_width / 2.0
+                    // Draw items, keeping those on deck in step with the interpolated ship.
+                    let on_ship = self.world.player_location == PlayerLocation::OnShip;
+                    for item in &self.world.items_in_game {
+                        let (mut item_x, mut item_y) = (trans_x, trans_y);
+                        if item.on_ship {
+                            if !on_ship {
+                                continue; // The ship isn't drawn either.
+                            }
+                            item_x += ship_x - self.world.ship.x;
+                            item_y += ship_y - self.world.ship.y;
+                        }
+                        if item.x - player_x > -w_width / 2.0 - IMAGE_SIZE_SCALED
+                            && item.x - player_x < w_width / 2.0
+                            && item.y - player_y > -w_width / 2.0
+                            && item.y - player_y < w_width / 2.0
                         {
-                            self.items_in_game[i].draw(
-                                &self.textures,
-                                &context,
-                                &mut graphics,
-                                trans_x,
-                                trans_y,
-                            );
+                            item.draw(&self.textures, &context, &mut graphics, item_x, item_y);
                         }
                     }
 
-                    match self.player_location {
-                        PlayerLocation::OnShip => self.ship.draw(
+                    match self.world.player_location {
+                        PlayerLocation::OnShip => self.world.ship.draw(
                             // Don't draw ship if player isn't on board.
                             &self.textures,
                             &context,
                             &mut graphics,
-                            trans_x,
-                            trans_y,
+                            trans_x + ship_x - self.world.ship.x,
+                            trans_y + ship_y - self.world.ship.y,
                         ),
                         PlayerLocation::InWorld => {}
                     }
 
-                    self.player
-                        .draw(&self.textures, &context, &mut graphics, w_width, w_height);
+                    // Outline the build cursor, green where the action would work.
+                    if let Some((i, j)) = self.world.build_target() {
+                        let (origin_x, origin_y) = if on_ship {
+                            (trans_x + ship_x, trans_y + ship_y)
+                        } else {
+                            (trans_x, trans_y)
+                        };
+                        let cell_x = origin_x + i as f64 * IMAGE_SIZE_SCALED;
+                        let cell_y = origin_y + j as f64 * IMAGE_SIZE_SCALED;
+                        let can_build = self.world.can_build();
+                        let can_unbuild = self.world.can_unbuild();
+
+                        // A ghost of the structure to be placed, tinted by whether it fits.
+                        let ghost = structure_image(&self.world.build_tile)
+                            .filter(|_| !on_ship && !can_unbuild);
+                        if let Some(ghost) = ghost {
+                            let tint = if can_build {
+                                [0.6, 1.0, 0.6, 0.6]
+                            } else {
+                                [1.0, 0.5, 0.5, 0.6]
+                            };
+                            Image::new_color(tint).draw(
+                                self.textures
+                                    .get(ghost)
+                                    .unwrap_or_else(|| panic!("Not found: {:?}", ghost)),
+                                &context.draw_state,
+                                context
+                                    .transform
+                                    .trans(cell_x, cell_y)
+                                    .scale(IMAGE_SCALE, IMAGE_SCALE),
+                                graphics,
+                            );
+                        }
+
+                        let color = if can_build || can_unbuild {
+                            [0.2, 0.9, 0.2, 1.0]
+                        } else {
+                            [0.9, 0.2, 0.2, 1.0]
+                        };
+                        Rectangle::new_border(color, 1.0).draw(
+                            [cell_x, cell_y, IMAGE_SIZE_SCALED, IMAGE_SIZE_SCALED],
+                            &context.draw_state,
+                            context.transform,
+                            graphics,
+                        );
+
+                        // Show what is being built and its cost, and on deck how the ship handles.
+                        let ship = &self.world.ship;
+                        let cost: Vec<String> = self.world
+                            .build_tile
+                            .build_cost()
+                            .iter()
+                            .map(|&(id, count)| format!("{} {}", count, id))
+                            .collect();
+                        let info = if on_ship {
+                            format!(
+                                "Building: {:?} ({})  Speed: {:.1}  Accel: {:.2}  \
+                                 Weight: {} / Lift: {}",
+                                self.world.build_tile,
+                                cost.join(", "),
+                                ship.max_speed,
+                                ship.acceleration,
+                                ship.weight,
+                                ship.lift
+                            )
+                        } else {
+                            format!(
+                                "Building: {:?}  Cost: {}",
+                                self.world.build_tile,
+                                cost.join(", ")
+                            )
+                        };
+                        text(
+                            [1.0; 4],
+                            16,
+                            &info,
+                            &mut self.glyphs,
+                            context.transform.trans(25.0, w_height - 25.0),
+                            graphics,
+                        ).unwrap_or_else(|_| panic!("Error drawing {}", info));
+                    }
+
+                    // Draw creatures, keeping the crew in step with the interpolated ship.
+                    for creature in &mut self.world.creatures {
+                        let (mut x, mut y) = (creature.x + trans_x, creature.y + trans_y);
+                        if creature.on_ship {
+                            if !on_ship {
+                                continue; // The ship isn't drawn either.
+                            }
+                            x += ship_x - self.world.ship.x;
+                            y += ship_y - self.world.ship.y;
+                        }
+                        let visible = x > -IMAGE_SIZE_SCALED && x < w_width
+                            && y > -IMAGE_SIZE_SCALED && y < w_height;
+                        if visible {
+                            creature.draw(&self.textures, &context, graphics, x, y);
+                        }
+                    }
+
+                    // Draw Player at center of screen.
+                    self.world.player.draw(
+                        &self.textures,
+                        &context,
+                        &mut graphics,
+                        w_width / 2.0,
+                        w_height / 2.0,
+                    );
+
+                    // Show the sword's sweep in front of the player mid-swing.
+                    if self.world.player.is_swinging() {
+                        let (cx, cy) = (w_width / 2.0, w_height / 2.0);
+                        let size = IMAGE_SIZE_SCALED;
+                        let sweep = match self.world.player.facing() {
+                            Direction::N => [cx - size / 2.0, cy - size, size * 2.0, size],
+                            Direction::E => [cx + size, cy - size / 2.0, size, size * 2.0],
+                            Direction::S => [cx - size / 2.0, cy + size, size * 2.0, size],
+                            Direction::W => [cx - size, cy - size / 2.0, size, size * 2.0],
+                        };
+                        rectangle([1.0, 1.0, 1.0, 0.35], sweep, context.transform, graphics);
+                    }
+
+                    // Show how worn down the tile being chopped or mined is.
+                    if let Some((x, y)) = self.world.gathering() {
+                        if let Some(tile) = self.world.map.tile(x, y) {
+                            let left = tile.health as f64 / tile.tile_type.max_health() as f64;
+                            let bar_x = trans_x + x as f64 * IMAGE_SIZE_SCALED;
+                            let bar_y = trans_y + y as f64 * IMAGE_SIZE_SCALED - 8.0;
+                            rectangle(
+                                [0.0, 0.0, 0.0, 0.6],
+                                [bar_x, bar_y, IMAGE_SIZE_SCALED, 4.0],
+                                context.transform,
+                                graphics,
+                            );
+                            rectangle(
+                                [0.9, 0.8, 0.3, 1.0],
+                                [bar_x, bar_y, IMAGE_SIZE_SCALED * left, 4.0],
+                                context.transform,
+                                graphics,
+                            );
+                        }
+                    }
 
                     // Draw health at top of screen.
-                    for i in 0..self.player.health {
+                    for i in 0..self.world.player.health {
                         image(
                             self.textures
                                 .get(IMG_HEART)
@@ -187,6 +341,106 @@ impl Game {
                         );
                     }
 
+                    // Draw food and stamina to the right of the hearts.
+                    let needs_x = 35.0
+                        + self.world.player.kind.max_health() as f64 * (IMAGE_SIZE_SCALED + 2.0);
+                    let needs = [
+                        (self.world.player.food / MAX_FOOD, [0.85, 0.55, 0.2, 1.0]),
+                        (self.world.player.stamina / MAX_STAMINA, [0.3, 0.75, 0.35, 1.0]),
+                    ];
+                    for (i, &(fraction, colour)) in needs.iter().enumerate() {
+                        let y = 27.0 + i as f64 * (IMAGE_SIZE_SCALED / 2.0);
+                        rectangle(
+                            [0.1, 0.1, 0.1, 1.0],
+                            [needs_x, y, 100.0, 10.0],
+                            context.transform,
+                            graphics,
+                        );
+                        rectangle(
+                            colour,
+                            [needs_x, y, 100.0 * fraction.clamp(0.0, 1.0), 10.0],
+                            context.transform,
+                            graphics,
+                        );
+                    }
+
+                    // Draw ship hull strength beneath health while aboard.
+                    if self.world.player_location == PlayerLocation::OnShip {
+                        let (hull, max_hull) = self.world.ship.hull();
+                        let hull_width = 120.0 * hull as f64 / max_hull.max(1) as f64;
+                        rectangle(
+                            [0.15, 0.1, 0.05, 1.0],
+                            [25.0, 30.0 + IMAGE_SIZE_SCALED, 120.0, 6.0],
+                            context.transform,
+                            graphics,
+                        );
+                        rectangle(
+                            [0.55, 0.35, 0.15, 1.0],
+                            [25.0, 30.0 + IMAGE_SIZE_SCALED, hull_width, 6.0],
+                            context.transform,
+                            graphics,
+                        );
+                    }
+
+                    // Draw the hotbar along the bottom, above any build info.
+                    let inventory = &self.world.player.inventory;
+                    let slot_size = IMAGE_SIZE_SCALED + 8.0;
+                    let hotbar_x = (w_width - slot_size * inventory.size() as f64) / 2.0;
+                    let hotbar_y = w_height - slot_size - 50.0;
+                    for index in 0..inventory.size() {
+                        let x = hotbar_x + index as f64 * slot_size;
+                        rectangle(
+                            [0.0, 0.0, 0.0, 0.5],
+                            [x, hotbar_y, slot_size, slot_size],
+                            context.transform,
+                            graphics,
+                        );
+                        let border = if index == inventory.selected() {
+                            [1.0, 0.85, 0.3, 1.0]
+                        } else {
+                            [0.6, 0.6, 0.6, 1.0]
+                        };
+                        Rectangle::new_border(border, 1.0).draw(
+                            [x, hotbar_y, slot_size, slot_size],
+                            &context.draw_state,
+                            context.transform,
+                            graphics,
+                        );
+                        if let Some(stack) = inventory.slot(index) {
+                            let img = stack.item.image();
+                            image(
+                                self.textures
+                                    .get(img)
+                                    .unwrap_or_else(|| panic!("Not found: {:?}", img)),
+                                context
+                                    .transform
+                                    .trans(x + 4.0, hotbar_y + 4.0)
+                                    .scale(IMAGE_SCALE, IMAGE_SCALE),
+                                graphics,
+                            );
+                            if stack.count > 1 {
+                                let count = stack.count.to_string();
+                                text(
+                                    [1.0; 4],
+                                    12,
+                                    &count,
+                                    &mut self.glyphs,
+                                    context.transform.trans(x + 3.0, hotbar_y + slot_size - 3.0),
+                                    graphics,
+                                ).unwrap_or_else(|_| panic!("Error drawing {}", count));
+                            }
+                        }
+                    }
+                    let load = format!("Load: {} / {}", inventory.weight(), inventory.capacity());
+                    text(
+                        [1.0; 4],
+                        12,
+                        &load,
+                        &mut self.glyphs,
+                        context.transform.trans(hotbar_x, hotbar_y - 4.0),
+                        graphics,
+                    ).unwrap_or_else(|_| panic!("Error drawing {}", load));
+
                     // End in-game graphics.
                 }
 
@@ -230,18 +484,50 @@ impl Game {
                             .scale(scale, scale),
                         graphics,
                     );
+
+                    // Title options.
+                    let mut options = vec!["Enter: New Game"];
+                    if self.can_continue {
+                        options.push("C: Continue");
+                    }
+                    let font = 24;
+                    for (i, option) in options.iter().enumerate() {
+                        text(
+                            [1.0; 4],
+                            font,
+                            option,
+                            &mut self.glyphs,
+                            context.transform.trans(
+                                w_width / 2.0 - 80.0,
+                                w_height - 80.0 + i as f64 * font as f64,
+                            ),
+                            graphics,
+                        ).expect(&format!("Error drawing {}", option));
+                    }
                 }
 
                 GameState::InMenu => {
                     // Display control options.
                     let transform_y = 100.0;
+                    let cargo_weight = format!(
+                        "H: Cargo weighs the ship down ({})",
+                        if self.world.ship.heavy_cargo { "on" } else { "off" }
+                    );
                     let draw_text = [
                         "Controls:",
                         "W/A/S/D: Movement",
                         "Tab: Enter/Exit this menu",
-                        "E: Use item/Interact",
+                        "E: Use item/Eat/Swing sword/Interact/Recruit/Chop and mine (hold)",
+                        "Shift: Sprint (uses stamina)",
                         "Space: Pickup/Drop item",
-                        "L: Owie :("
+                        "1-8: Select hotbar slot",
+                        "F: Throw item",
+                        "C: Crafting",
+                        "E on a chest or cargo hold: Storage",
+                        cargo_weight.as_str(),
+                        "B: Build mode (arrows aim, 1-7: Part or structure, E: Build, Q: Remove)",
+                        "F5: Save game",
+                        "F6: Export ship blueprint",
                     ];
                     let font = 24;
                     for i in 0..draw_text.len() {
@@ -256,6 +542,144 @@ impl Game {
                             graphics,
                         ).expect(&format!("Error drawing {}", draw_text[i]));
                     }
+
+                    // The crew roster, below the controls.
+                    let mut roster = vec![
+                        "Crew (Up/Down: Select, Left/Right: Change station):".to_string(),
+                    ];
+                    let crew: Vec<_> =
+                        self.world.creatures.iter().filter(|c| c.recruited).collect();
+                    if crew.is_empty() {
+                        roster.push("No crew yet - press E beside a stranger.".to_string());
+                    }
+                    for (i, member) in crew.iter().enumerate() {
+                        let marker = if i == self.roster_selection { ">" } else { " " };
+                        let station = match member.station {
+                            Some(station) => format!("{:?}", station),
+                            None => "Free".to_string(),
+                        };
+                        let place = if member.on_ship { "aboard" } else { "ashore" };
+                        roster.push(format!("{} Crew {}: {}, {}", marker, i + 1, station, place));
+                    }
+                    let roster_y = transform_y + (draw_text.len() + 1) as f64 * font as f64;
+                    for (i, line) in roster.iter().enumerate() {
+                        text(
+                            [1.0; 4],
+                            font,
+                            line,
+                            &mut self.glyphs,
+                            context
+                                .transform
+                                .trans(100.0, roster_y + i as f64 * font as f64),
+                            graphics,
+                        ).unwrap_or_else(|_| panic!("Error drawing {}", line));
+                    }
+                }
+
+                GameState::Crafting => {
+                    let mut lines = vec![
+                        "Crafting (Up/Down: Select, E: Craft, C: Close):".to_string(),
+                    ];
+                    for (i, recipe) in self.world.recipes.recipes.iter().enumerate() {
+                        let marker = if i == self.recipe_selection { ">" } else { " " };
+                        let stacks = |stacks: &[(String, u32)]| {
+                            let names: Vec<_> = stacks
+                                .iter()
+                                .map(|(id, count)| format!("{} {}", count, id))
+                                .collect();
+                            names.join(", ")
+                        };
+                        let mut line = format!(
+                            "{} {}: {} -> {}",
+                            marker,
+                            recipe.name,
+                            stacks(&recipe.inputs),
+                            stacks(&recipe.outputs)
+                        );
+                        if let Some(ref station) = recipe.station {
+                            line.push_str(&format!(" (at {:?})", station));
+                        }
+                        lines.push(line);
+                    }
+                    let font = 24;
+                    for (i, line) in lines.iter().enumerate() {
+                        // Recipes that can't be made right now are greyed out.
+                        let recipes = &self.world.recipes.recipes;
+                        let craftable = i == 0 || self.world.can_craft(&recipes[i - 1]);
+                        let color = if craftable { [1.0; 4] } else { [0.5, 0.5, 0.5, 1.0] };
+                        text(
+                            color,
+                            font,
+                            line,
+                            &mut self.glyphs,
+                            context.transform.trans(100.0, 100.0 + i as f64 * font as f64),
+                            graphics,
+                        ).unwrap_or_else(|_| panic!("Error drawing {}", line));
+                    }
+                }
+
+                GameState::Storage => {
+                    let font = 20;
+                    let header = "Storage (Up/Down: Select, Left/Right: Switch side, \
+                                  E: Move stack, Q: Close)";
+                    text(
+                        [1.0; 4],
+                        font,
+                        header,
+                        &mut self.glyphs,
+                        context.transform.trans(100.0, 100.0),
+                        graphics,
+                    ).unwrap_or_else(|_| panic!("Error drawing {}", header));
+
+                    // The player's inventory beside the container's, a slot per line.
+                    let container = self.world.open_container;
+                    let name = match container {
+                        Some(Container::Chest(..)) => "Chest",
+                        Some(Container::Hold(..)) => "Cargo hold",
+                        None => "",
+                    };
+                    let sides = [
+                        ("Carried", Some(&self.world.player.inventory)),
+                        (name, container.and_then(|c| self.world.container(c))),
+                    ];
+                    let mut columns = vec![];
+                    for (column, &(title, inventory)) in sides.iter().enumerate() {
+                        let mut lines = vec![];
+                        if let Some(inventory) = inventory {
+                            lines.push(format!(
+                                "{} (Load: {} / {})",
+                                title,
+                                inventory.weight(),
+                                inventory.capacity()
+                            ));
+                            for index in 0..inventory.size() {
+                                let selected = column == self.storage_column
+                                    && index == self.storage_selection;
+                                let marker = if selected { ">" } else { " " };
+                                let contents = match inventory.slot(index) {
+                                    Some(stack) => format!("{} {}", stack.count, stack.item.id),
+                                    None => "-".to_string(),
+                                };
+                                lines.push(format!("{} {}: {}", marker, index + 1, contents));
+                            }
+                        }
+                        columns.push(lines);
+                    }
+                    for (column, lines) in columns.iter().enumerate() {
+                        for (i, line) in lines.iter().enumerate() {
+                            text(
+                                [1.0; 4],
+                                font,
+                                line,
+                                &mut self.glyphs,
+                                context.transform.trans(
+                                    100.0 + column as f64 * 300.0,
+                                    100.0 + (i + 2) as f64 * font as f64,
+                                ),
+                                graphics,
+                            ).unwrap_or_else(|_| panic!("Error drawing {}", line));
+                        }
+                    }
                 }
 
                 GameState::GameOver => {
@@ -274,6 +698,20 @@ impl Game {
                     ).expect(&format!("Error drawing {}", draw_text));
                 }
             }
+
+            // Show any recent notice over whichever screen is up.
+            if let Some((ref message, given)) = self.notice {
+                if given.elapsed() < NOTICE_DURATION {
+                    text(
+                        [1.0, 0.45, 0.45, 1.0],
+                        16,
+                        message,
+                        &mut self.glyphs,
+                        context.transform.trans(25.0, 90.0),
+                        graphics,
+                    ).unwrap_or_else(|_| panic!("Error drawing {}", message));
+                }
+            }
         });
     }
 
@@ -283,28 +721,24 @@ impl Game {
         @param window The PistonWindow that is drawn to.
     */
     pub fn run(&mut self, window: &mut PistonWindow) {
-        self.ship.x = MAP_WIDTH as f64 * IMAGE_SIZE_SCALED / 2.0; // Initial ship position.
-        self.ship.y = MAP_HEIGHT as f64 * IMAGE_SIZE_SCALED / 2.0;
-        self.player.x = self.ship.x + ((self.ship.width / 2.0) * IMAGE_SIZE_SCALED);
-        self.player.y = self.ship.y + ((self.ship.height / 2.0) * IMAGE_SIZE_SCALED);
-
-        // Temporary item generation.
-        self.items_in_game.push(
-            self.item_prototypes
-                .get("bisket")
-                .unwrap()
-                .generate_clone(self.ship.x + 128.0, self.ship.y + 128.0),
-        );
+        self.store_previous_positions();
 
+        let mut last_update = Instant::now();
         while let Some(e) = window.next() {
             match e {
                 Event::Input(Input::Button(args)) => {
                     self.handle_input(&args.state, &args.button);
                 }
 
-                // TODO Add lag handler here
+                // Update events only drive the clock; the timestep decides how many ticks run.
                 Event::Loop(Loop::Update(_args)) => {
-                    self.update();
+                    let now = Instant::now();
+                    let elapsed = now.duration_since(last_update).as_secs_f64();
+                    last_update = now;
+                    for _ in 0..self.timestep.advance(elapsed) {
+                        self.store_previous_positions();
+                        self.update();
+                    }
                 }
 
                 Event::Loop(Loop::Render(_args)) => {
@@ -316,381 +750,300 @@ impl Game {
     }
 
     /*
-        Handles updating of the Game, mosti mportantly player position.
+        Remembers where the player and ship are before a tick, for interpolated drawing.
     */
-    fn update(&mut self) {
-        if self.updates_since_last_gen > 1000 {
-            self.updates_since_last_gen = 0;
-
-            let mut rng = thread_rng();
-            let mut range_x = distributions::Range::new(-1.0 * self.w_width/2.0, self.w_width/2.0);
-            let mut range_y = distributions::Range::new(-1.0 * self.w_height/2.0, self.w_height/2.0);
-
-            let item_x = self.player.x + range_x.sample(&mut rng);
-            let item_y = self.player.y + range_y.sample(&mut rng);
-
-            self.items_in_game.push(
-                self.item_prototypes
-                    .get("bisket")
-                    .unwrap()
-                    .generate_clone(item_x, item_y),
-            );
-        }
-        self.updates_since_last_gen+=1;
+    fn store_previous_positions(&mut self) {
+        self.prev_player = (self.world.player.x, self.world.player.y);
+        self.prev_ship = (self.world.ship.x, self.world.ship.y);
+    }
 
-        if self.player.is_dead() {
+    /*
+        Steps the world while playing, ending the game once the player dies.
+    */
+    fn update(&mut self) {
+        if self.world.is_game_over() {
             self.game_state = GameState::GameOver;
         }
         if self.game_state == GameState::InGame {
-            match self.player_location {
-                PlayerLocation::OnShip => {
-                    self.player.other_vel_x = self.ship.self_vel_x;
-                    self.player.other_vel_y = self.ship.self_vel_y;
-                    self.player.update_position_other();
-                    let x = self.player.x_to_be_location();
-                    let y = self.player.y_to_be_location();
-                    if self.is_on_ship(x, y) {
-                        self.player.update_position_self();
-                        self.player.update_direction();
-                    }
-                    self.ship.update_position();
+            self.world.update();
+        }
+    }
+
+    /*
+        Input Handling from user.
+
+        @param state The ButtonState.
+        @param button The input button arguments.
+    */
+    fn handle_input(&mut self, state: &ButtonState, button: &Button) {
+        use self::Key::*;
+        match *button {
+            Button::Keyboard(key) => match key {
+                // Menu toggle.
+                Return | Tab => self.execute_open_menu(state),
+                C if self.game_state == GameState::Title => self.execute_continue(state),
+                C => self.execute_open_crafting(state),
+                F5 => self.execute_save(state),
+                F6 => self.execute_export_ship(state),
+                Up | Down | Left | Right if self.game_state == GameState::InMenu => {
+                    self.execute_roster(state, key)
+                }
+                Up | Down | E if self.game_state == GameState::Crafting => {
+                    self.execute_crafting(state, key)
+                }
+                Up | Down | Left | Right | E if self.game_state == GameState::Storage => {
+                    self.execute_storage(state, key)
                 }
-                PlayerLocation::InWorld => {
-                    let x = self.player.x_to_be_location();
-                    let y = self.player.y_to_be_location();
-                    if self.can_go_to(x, y) {
-                        self.player.update_position_self();
-                        self.player.update_direction();
+                Q if self.game_state == GameState::Storage => self.execute_close_storage(state),
+                H if self.game_state == GameState::InMenu => {
+                    self.execute_toggle_cargo_weight(state)
+                }
+                // Everything else is played out in the world.
+                _ => {
+                    if self.game_state == GameState::InGame {
+                        // Editing the ship can move its origin; carry the
+                        // interpolation along so it doesn't jump.
+                        let (ship_x, ship_y) = (self.world.ship.x, self.world.ship.y);
+                        self.world.handle_input(state, key);
+                        self.prev_ship.0 += self.world.ship.x - ship_x;
+                        self.prev_ship.1 += self.world.ship.y - ship_y;
+                        if self.world.open_container.is_some() {
+                            self.game_state = GameState::Storage;
+                            self.storage_column = 0;
+                            self.storage_selection = 0;
+                        }
                     }
                 }
-            }
+            },
+            _ => {}
         }
     }
 
     /*
-        Determines the tile the player is standing on.
+        Opens the menu and changes Game State respectively.
 
-        @return Option<TileType> Some tile.
+        @param state The Button State (e.g. pressed).
     */
-    fn tile_under_player(&self, location: PlayerLocation) -> Option<Tile> {
-        let x = self.player.x + IMAGE_SIZE_SCALED as f64 / 2.0;
-        let y = self.player.y + IMAGE_SIZE_SCALED as f64 / 2.0;
-        let iss = IMAGE_SIZE_SCALED as f64;
-        match location {
-            PlayerLocation::OnShip => {
-                let is_in_x = x >= self.ship.x && x + iss <= self.ship.x + self.ship.width * iss;
-                let is_in_y = y >= self.ship.y && y + iss <= self.ship.y + self.ship.height * iss;
-                if is_in_x && is_in_y {
-                    return Some(
-                        self.ship.tiles[((x - self.ship.x) / IMAGE_SIZE_SCALED).floor() as usize]
-                            [((y - self.ship.y) / IMAGE_SIZE_SCALED).floor() as usize]
-                            .clone(),
-                    );
+    fn execute_open_menu(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press {
+            match self.game_state {
+                GameState::Title => {
+                    self.game_state = GameState::InMenu;
                 }
-            }
-            PlayerLocation::InWorld => {
-                let is_in_x = x >= 0.0 && x + iss <= MAP_WIDTH as f64 * iss;
-                let is_in_y = y >= 0.0 && y + iss <= MAP_HEIGHT as f64 * iss;
-                if is_in_x && is_in_y {
-                    return Some(
-                        self.map.tiles[(x / IMAGE_SIZE_SCALED).floor() as usize]
-                            [(y / IMAGE_SIZE_SCALED).floor() as usize]
-                            .clone(),
-                    );
+                GameState::InGame => {
+                    self.game_state = GameState::InMenu;
                 }
+                GameState::InMenu => {
+                    self.game_state = GameState::InGame;
+                }
+                _ => {}
             }
         }
-        None
     }
 
     /*
-        Determines whether some x and y can be stepped on.
+        Picks a crew member on the roster, or changes their station.
 
-        @param x Some x coordinate.
-        @param y Some y coordinate.
-        @return bool Whether the respective tile is passable.
+        @param state The Button State (e.g. pressed).
+        @param key The arrow key pressed.
     */
-    fn can_go_to(&self, x: f64, y: f64) -> bool {
-        let iss = IMAGE_SIZE_SCALED as f64;
-        let is_in_x = x >= 0.0 && x + iss <= MAP_WIDTH as f64 * iss;
-        let is_in_y = y >= 0.0 && y + iss <= MAP_HEIGHT as f64 * iss;
-        if is_in_x && is_in_y {
-            let x = x / iss;
-            let y = y / iss;
-            if self.map.tiles[x.floor() as usize][y.floor() as usize].passable // Determines where x,y is on the map.
-                && self.map.tiles[x.floor() as usize][y.ceil() as usize].passable
-                && self.map.tiles[x.ceil() as usize][y.floor() as usize].passable
-                && self.map.tiles[x.ceil() as usize][y.ceil() as usize].passable
-            {
-                return true;
-            }
+    fn execute_roster(&mut self, state: &ButtonState, key: Key) {
+        if *state != ButtonState::Press {
+            return;
+        }
+        let mut crew: Vec<_> = self.world.creatures.iter_mut().filter(|c| c.recruited).collect();
+        if crew.is_empty() {
+            return;
+        }
+        let count = crew.len();
+        self.roster_selection = self.roster_selection.min(count - 1);
+        match key {
+            Key::Up => self.roster_selection = (self.roster_selection + count - 1) % count,
+            Key::Down => self.roster_selection = (self.roster_selection + 1) % count,
+            Key::Left => crew[self.roster_selection].cycle_station(false),
+            Key::Right => crew[self.roster_selection].cycle_station(true),
+            _ => {}
         }
-        false
     }
 
     /*
-        Checks whether a specific x,y position is on the ship.
-        Used for collision detection.
+        Opens or closes the crafting screen while playing.
 
-        @param x Some x coordinate.
-        @param y Some y coordinate.
-        @return bool Whether the x,y coordinate is within the ship.
+        @param state The Button State (e.g. pressed).
     */
-    fn is_on_ship(&self, x: f64, y: f64) -> bool {
-        let ship_x = self.ship.x_to_be_location(); // Extraneous for moving ship.
-        let ship_y = self.ship.y_to_be_location();
-
-        // Check edges.
-        let is_in_x =
-            x >= ship_x && x + IMAGE_SIZE_SCALED <= ship_x + self.ship.width * IMAGE_SIZE_SCALED;
-        let is_in_y =
-            y >= ship_y && y + IMAGE_SIZE_SCALED <= ship_y + self.ship.height * IMAGE_SIZE_SCALED;
-        if is_in_x && is_in_y {
-            // Check surrounding tiles.
-            let ship_tile_x = (x - ship_x) / IMAGE_SIZE_SCALED;
-            let ship_tile_y = (y - ship_y) / IMAGE_SIZE_SCALED;
-            if self.ship.tiles[ship_tile_x.floor() as usize][ship_tile_y.floor() as usize].passable
-                && self.ship.tiles[ship_tile_x.floor() as usize][ship_tile_y.ceil() as usize]
-                    .passable
-                && self.ship.tiles[ship_tile_x.ceil() as usize][ship_tile_y.floor() as usize]
-                    .passable
-                && self.ship.tiles[ship_tile_x.ceil() as usize][ship_tile_y.ceil() as usize]
-                    .passable
-            {
-                return true;
+    fn execute_open_crafting(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press {
+            match self.game_state {
+                GameState::InGame => self.game_state = GameState::Crafting,
+                GameState::Crafting => self.game_state = GameState::InGame,
+                _ => {}
             }
         }
-        false
     }
 
     /*
-        Input Handling from user.
+        Picks a recipe on the crafting screen, or crafts it.
 
-        @param state The ButtonState.
-        @param button The input button arguments.
+        @param state The Button State (e.g. pressed).
+        @param key The key pressed.
     */
-    fn handle_input(&mut self, state: &ButtonState, button: &Button) {
-        use self::Key::*;
-        match *button {
-            Button::Keyboard(key) => match key {
-                // Menu toggle.
-                Return | Tab => self.execute_open_menu(state),
-                // Moving.
-                W | A | S | D => self.execute_move(state, &Some(key)),
-                E => self.execute_action(state),
-                L => {
-                    if *state == ButtonState::Press {
-                        self.player.take_damage(1)
-                    }
-                }
-                Space => {
-                    self.execute_player_hands(state);
-                }
-                _ => {}
-            },
+    fn execute_crafting(&mut self, state: &ButtonState, key: Key) {
+        let count = self.world.recipes.recipes.len();
+        if *state != ButtonState::Press || count == 0 {
+            return;
+        }
+        self.recipe_selection = self.recipe_selection.min(count - 1);
+        match key {
+            Key::Up => self.recipe_selection = (self.recipe_selection + count - 1) % count,
+            Key::Down => self.recipe_selection = (self.recipe_selection + 1) % count,
+            Key::E => {
+                self.world.craft(self.recipe_selection);
+            }
             _ => {}
         }
     }
 
     /*
-        Changes player position in the world.
+        Picks a slot on the storage screen, or moves its stack to the other side.
 
-        @param state State of the toggled button (e.g. pressed).
+        @param state The Button State (e.g. pressed).
+        @param key The key pressed.
     */
-    fn change_player_location(&mut self) {
-        self.player_location = match self.player_location {
-            PlayerLocation::OnShip => PlayerLocation::InWorld,
-            PlayerLocation::InWorld => PlayerLocation::OnShip,
+    fn execute_storage(&mut self, state: &ButtonState, key: Key) {
+        let container = match self.world.open_container {
+            Some(container) => container,
+            None => return,
         };
-        self.player.creature_state = CreatureState::Normal;
+        if *state != ButtonState::Press {
+            return;
+        }
+        let stored = self.world.container(container).map_or(0, |c| c.size());
+        let sizes = [self.world.player.inventory.size(), stored];
+        match key {
+            Key::Left | Key::Right => self.storage_column = 1 - self.storage_column,
+            _ => {}
+        }
+        let count = sizes[self.storage_column].max(1);
+        self.storage_selection = self.storage_selection.min(count - 1);
+        match key {
+            Key::Up => self.storage_selection = (self.storage_selection + count - 1) % count,
+            Key::Down => self.storage_selection = (self.storage_selection + 1) % count,
+            Key::E if self.storage_column == 0 => {
+                self.world.store(self.storage_selection);
+            }
+            Key::E => {
+                self.world.retrieve(self.storage_selection);
+            }
+            _ => {}
+        }
     }
 
     /*
-        Handles "pickup" and "drop" for the player.
+        Closes the storage screen and goes back to playing.
 
         @param state The Button State (e.g. pressed).
     */
-    fn execute_player_hands(&mut self, state: &ButtonState) {
+    fn execute_close_storage(&mut self, state: &ButtonState) {
         if *state == ButtonState::Press {
-            match self.player.inventory {
-                Some(_) => {
-                    // Dropping an item.
-                    let item = self.player.drop_item().expect("dropped empty inventory");
-                    self.items_in_game.push(item);
-                }
-                None => {
-                    let mut place = -1;
-                    for i in 0..self.items_in_game.len() {
-                        // Determines if player is in range of item.
-                        let diff_x = self.items_in_game[i].x - self.player.x;
-                        let diff_y = self.items_in_game[i].y - self.player.y;
-                        if diff_x < IMAGE_SIZE_SCALED && diff_x > -IMAGE_SIZE_SCALED
-                            && diff_y < IMAGE_SIZE_SCALED
-                            && diff_y > -IMAGE_SIZE_SCALED
-                        {
-                            place = i as i32;
-                            break;
-                        }
-                    }
-                    if place != -1 {
-                        // Add item to inventory.
-                        let item = self.items_in_game.remove(place as usize);
-                        self.player.pickup_item(item);
-                    }
-                }
-            }
+            self.world.open_container = None;
+            self.game_state = GameState::InGame;
         }
     }
 
     /*
-        Opens the menu and changes Game State respectively.
+        Switches whether cargo weighs the ship down.
 
         @param state The Button State (e.g. pressed).
     */
-    fn execute_open_menu(&mut self, state: &ButtonState) {
+    fn execute_toggle_cargo_weight(&mut self, state: &ButtonState) {
         if *state == ButtonState::Press {
-            match self.game_state {
-                GameState::Title => {
-                    self.game_state = GameState::InMenu;
-                }
-                GameState::InGame => {
-                    self.game_state = GameState::InMenu;
-                }
-                GameState::InMenu => {
-                    self.game_state = GameState::InGame;
-                }
-                _ => {}
-            }
+            let ship = &mut self.world.ship;
+            ship.heavy_cargo = !ship.heavy_cargo;
+            ship.recompute_stats();
         }
     }
 
     /*
-        Moves the player / ship, depending on player control state.
-        Calls respective ship / player functionality to handle.
+        Continues from the save file when on the title screen.
 
-        @param state Button State (e.g. pressed or released).
-        @param key Some key that has been pressed.
+        @param state The Button State (e.g. pressed).
     */
-    fn execute_move(&mut self, state: &ButtonState, key: &Option<Key>) {
-        if self.game_state == GameState::InGame {
-            match self.player.creature_state {
-                CreatureState::Normal => {
-                    self.player.handle_input(&state, &key);
-                    self.player.update_self_velocity();
-                }
-                CreatureState::ControllingShip => {
-                    self.ship.handle_input(&state, &key);
-                    self.ship.update_self_velocity();
-                }
+    fn execute_continue(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press && self.game_state == GameState::Title && self.can_continue
+        {
+            match self.load_game(SAVE_FILE) {
+                Ok(()) => self.game_state = GameState::InGame,
+                Err(e) => self.notify(format!("Could not load {}: {}", SAVE_FILE, e)),
             }
         }
     }
 
     /*
-        Handles player general "action" button, with varying results (a sort of catch all).
+        Saves the game while playing.
 
         @param state The Button State (e.g. pressed).
     */
-    fn execute_action(&mut self, state: &ButtonState) {
-        if self.game_state == GameState::InGame {
-            if *state == ButtonState::Press {
-                let current_location = self.player_location.clone();
-                match self.tile_under_player(current_location) {
-                    Some(t) => match t.tile_type {
-                        TileType::Portal => match self.player_location {
-                            PlayerLocation::OnShip => {
-                                if let Some(tile) = self.tile_under_player(PlayerLocation::InWorld)
-                                {
-                                    if tile.passable {
-                                        self.change_player_location();
-                                        let x = (self.player.x + IMAGE_SIZE_SCALED as f64 / 2.0)
-                                            / IMAGE_SIZE_SCALED;
-                                        let y = (self.player.y + IMAGE_SIZE_SCALED as f64 / 2.0)
-                                            / IMAGE_SIZE_SCALED;
-                                        self.player.x = x.floor() * IMAGE_SIZE_SCALED;
-                                        self.player.y = y.floor() * IMAGE_SIZE_SCALED;
-
-                                        let x = self.player.x + IMAGE_SIZE_SCALED as f64 / 2.0;
-                                        let y = self.player.y + IMAGE_SIZE_SCALED as f64 / 2.0;
-                                        let temp = self.map.tiles
-                                            [(x / IMAGE_SIZE_SCALED).floor() as usize]
-                                            [(y / IMAGE_SIZE_SCALED).floor() as usize]
-                                            .clone();
-
-                                        self.map.under_portal = temp;
-                                        self.map.tiles[(x / IMAGE_SIZE_SCALED).floor() as usize]
-                                            [(y / IMAGE_SIZE_SCALED).floor() as usize] =
-                                            Tile::new(TileType::Portal);
-                                    }
-                                }
-                            }
-                            PlayerLocation::InWorld => {
-                                if let Some(tile) = self.tile_under_player(PlayerLocation::OnShip) {
-                                    if tile.passable {
-                                        self.change_player_location();
-                                        let x = (self.player.x + IMAGE_SIZE_SCALED as f64 / 2.0)
-                                            / IMAGE_SIZE_SCALED;
-                                        let y = (self.player.y + IMAGE_SIZE_SCALED as f64 / 2.0)
-                                            / IMAGE_SIZE_SCALED;
-                                        self.player.x = x.floor() * IMAGE_SIZE_SCALED;
-                                        self.player.y = y.floor() * IMAGE_SIZE_SCALED;
-
-                                        let x = self.player.x + IMAGE_SIZE_SCALED as f64 / 2.0;
-                                        let y = self.player.y + IMAGE_SIZE_SCALED as f64 / 2.0;
-
-                                        self.map.tiles[(x / IMAGE_SIZE_SCALED).floor() as usize]
-                                            [(y / IMAGE_SIZE_SCALED).floor() as usize] =
-                                            self.map.under_portal.clone();
-                                        self.map.under_portal = Tile::new(TileType::Air);
-                                    }
-                                }
-                            }
-                        },
+    fn execute_save(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press
+            && (self.game_state == GameState::InGame || self.game_state == GameState::InMenu)
+        {
+            match self.save_game(SAVE_FILE) {
+                Ok(()) => self.can_continue = true,
+                Err(e) => self.notify(format!("Could not save {}: {}", SAVE_FILE, e)),
+            }
+        }
+    }
 
-                        TileType::Wheel => {
-                            self.player.change_control_state();
-                            self.ship.reset_dir();
-                        }
-                        _ => self.player.use_item(),
-                    },
-                    _ => {
-                        self.player.use_item();
-                    }
-                }
+    /*
+        Tells the player something went wrong, on screen for a while and on stderr.
+
+        @param message What went wrong.
+    */
+    fn notify(&mut self, message: String) {
+        eprintln!("{}", message);
+        self.notice = Some((message, Instant::now()));
+    }
+
+    /*
+        Exports the current ship as a blueprint, for use as a ship design.
+
+        @param state The Button State (e.g. pressed).
+    */
+    fn execute_export_ship(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press
+            && (self.game_state == GameState::InGame || self.game_state == GameState::InMenu)
+        {
+            let exported = blueprint::export(&self.world.ship, "Exported Airship")
+                .and_then(|text| fs::write(EXPORT_FILE, text).map_err(DataError::from));
+            if let Err(e) = exported {
+                self.notify(format!("Could not export {}: {}", EXPORT_FILE, e));
             }
         }
     }
-}
 
-/*
-    Creates prototypes of items for prototyping pattern.
+    /*
+        Writes the world to a save file.
 
-    @return HashMap<String, Item> A map of string to items.
-*/
-fn generate_item_prototypes() -> HashMap<String, Item> {
-    let mut prototypes: HashMap<String, Item> = HashMap::new();
-    prototypes.insert(
-        "bisket".to_string(),
-        Item::new(ItemType::Food(FoodType::Bisket), 1, true, 1.0),
-    );
-    prototypes.insert(
-        "sword".to_string(),
-        Item::new(
-            ItemType::Interactable(InteractableType::Sword),
-            10,
-            true,
-            5.0,
-        ),
-    );
-    prototypes.insert(
-        "grune".to_string(),
-        Item::new(ItemType::Resource(ResourceType::Grune), 1, true, 0.5),
-    );
-    prototypes.insert(
-        "logs".to_string(),
-        Item::new(ItemType::Resource(ResourceType::Logs), 5, true, 8.0),
-    );
-    prototypes
+        @param path Where to write the save.
+        @return Result<(), DataError> Whether the save was written.
+    */
+    pub fn save_game(&self, path: &str) -> Result<(), DataError> {
+        save::save_to_file(path, &self.world)
+    }
+
+    /*
+        Replaces the world with one from a save file.
+
+        @param path Where to read the save from.
+        @return Result<(), DataError> Whether the save was loaded.
+    */
+    pub fn load_game(&mut self, path: &str) -> Result<(), DataError> {
+        let recipes = self.world.recipes.clone();
+        self.world = save::load_from_file(path, &self.world.item_prototypes)?;
+        self.world.recipes = recipes;
+        self.store_previous_positions();
+        Ok(())
+    }
 }
 
 /* 
@@ -709,13 +1062,89 @@ fn generate_glyphs(window: &mut PistonWindow) -> Glyphs {
     glyphs
 }
 
+/*
+    Loads the starting ship from its blueprint in the assets folder. Falls back to
+    the built-in copy if that fails.
+
+    @return Ship The starting ship.
+*/
+fn load_default_ship() -> Ship {
+    let loaded = Search::ParentsThenKids(3, 3)
+        .for_folder("ships")
+        .map_err(|e| e.to_string())
+        .and_then(|ships| {
+            Blueprint::from_file(ships.join("default.ship")).map_err(|e| e.to_string())
+        });
+    let blueprint = match loaded {
+        Ok(blueprint) => blueprint,
+        Err(e) => {
+            eprintln!("Could not load default.ship, using built-in ship: {}", e);
+            Blueprint::parse(DEFAULT_BLUEPRINT).expect("Error in default ship blueprint")
+        }
+    };
+    blueprint.build()
+}
+
+/*
+    Loads the item definitions from the data folder. Falls back to the built-in
+    definitions if that fails, or if any item the world needs is left out.
+
+    @return HashMap<String, Item> The item prototypes.
+*/
+fn load_items() -> HashMap<String, Item> {
+    let loaded = Search::ParentsThenKids(3, 3)
+        .for_folder("data")
+        .map_err(|e| e.to_string())
+        .and_then(|data| item::load_items(data.join("items.txt")).map_err(|e| e.to_string()))
+        .and_then(|items| match item::check_required(&items, &world::required_items()) {
+            Ok(()) => Ok(items),
+            Err(e) => Err(e.to_string()),
+        });
+    match loaded {
+        Ok(items) => items,
+        Err(e) => {
+            eprintln!("Could not load items.txt, using built-in items: {}", e);
+            item::generate_item_prototypes()
+        }
+    }
+}
+
+/*
+    Loads the crafting recipes from the data folder. Falls back to the built-in
+    copy if that fails.
+
+    @param world The world, whose item prototypes the recipes refer to.
+    @return RecipeBook The recipes.
+*/
+fn load_recipes(world: &World) -> RecipeBook {
+    let loaded = Search::ParentsThenKids(3, 3)
+        .for_folder("data")
+        .map_err(|e| e.to_string())
+        .and_then(|data| {
+            RecipeBook::from_file(data.join("recipes.txt"), &world.item_prototypes)
+                .map_err(|e| e.to_string())
+        });
+    match loaded {
+        Ok(recipes) => recipes,
+        Err(e) => {
+            eprintln!("Could not load recipes.txt, using built-in recipes: {}", e);
+            RecipeBook::parse(DEFAULT_RECIPES, &world.item_prototypes)
+                .expect("Error in default recipes")
+        }
+    }
+}
+
 /*
     Compiles all of the textures (sprites, etc.) and loads them into a Hash Map.
 
     @param window The game window.
+    @param items The item prototypes, whose sprites are loaded too.
     @return HashMap<String, G2dTexture> The mapped images.
 */
-fn generate_textures(window: &mut PistonWindow) -> HashMap<String, G2dTexture> {
+fn generate_textures(
+    window: &mut PistonWindow,
+    items: &HashMap<String, Item>,
+) -> HashMap<String, G2dTexture> {
     // Collect the graphics ("textures").
     let assets = Search::ParentsThenKids(3, 3)
         .for_folder("images")
@@ -725,15 +1154,31 @@ fn generate_textures(window: &mut PistonWindow) -> HashMap<String, G2dTexture> {
         IMG_CLOUD_1,
         IMG_CLOUD_2,
         IMG_WOOD_FLOOR,
+        IMG_WOOD_WALL_POST,
+        IMG_WOOD_WALL_END,
+        IMG_WOOD_WALL_STRAIGHT,
+        IMG_WOOD_WALL_CORNER,
+        IMG_WOOD_WALL_TEE,
+        IMG_WOOD_WALL_CROSS,
+        IMG_DOOR,
+        IMG_CHEST,
         IMG_WHEEL,
         IMG_TREE,
         IMG_GRUNE,
         IMG_PORTAL,
+        IMG_SAIL,
+        IMG_PROPELLER,
+        IMG_BALLOON,
+        IMG_CARGO_HOLD,
+        IMG_WORKBENCH,
+        IMG_REPAIR_POST,
         IMG_HEART,
-        IMG_ITEM_BISKET,
-        IMG_ITEM_SWORD,
-        IMG_ITEM_GRUNE,
-        IMG_ITEM_LOGS,
+        IMG_WILDLIFE_1,
+        IMG_WILDLIFE_2,
+        IMG_WILDLIFE_3,
+        IMG_MONSTER_1,
+        IMG_MONSTER_2,
+        IMG_MONSTER_3,
         IMG_STONE_WALL,
         IMG_STONE_WALL_TEXTURE,
         IMG_STONE_WALL_EDGE_1_SIDE,
@@ -787,7 +1232,8 @@ fn generate_textures(window: &mut PistonWindow) -> HashMap<String, G2dTexture> {
 
     let ts = TextureSettings::new().filter(Filter::Nearest);
 
-    for image_name in image_names.into_iter() {
+    let item_sprites = items.values().map(|item| item.image());
+    for image_name in image_names.iter().cloned().chain(item_sprites) {
         let filename = image_name.to_owned().to_owned() + ".png";
         let img = Texture::from_path(
             &mut window.factory,
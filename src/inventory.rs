@@ -0,0 +1,270 @@
+//! The inventory holds the items a creature carries, in a row of slots.
+//! Like items share a slot up to their kind's stack limit, and everything carried
+//! counts against a weight capacity. One slot is selected at a time - that is the
+//! item in hand, used and dropped by the player's controls.
+
+use item::Item;
+
+/**
+    A pile of like items sharing a slot.
+
+    @field item One of the items; the rest are copies of it.
+    @field count How many items are in the pile.
+*/
+#[derive(Clone)]
+pub struct Stack {
+    pub item: Item,
+    pub count: u32,
+}
+
+/**
+    Implementation of the Inventory object.
+
+    @field slots The slots, each empty or holding a stack.
+    @field selected Which slot is in hand.
+    @field capacity The most weight that can be carried.
+*/
+pub struct Inventory {
+    slots: Vec<Option<Stack>>,
+    selected: usize,
+    capacity: f64,
+}
+
+impl Inventory {
+    /*
+        Inventory constructor. The first slot starts out selected.
+
+        @param slots How many slots there are.
+        @param capacity The most weight that can be carried.
+        @return Inventory Returns itself.
+    */
+    pub fn new(slots: usize, capacity: f64) -> Self {
+        Inventory {
+            slots: vec![None; slots],
+            selected: 0,
+            capacity,
+        }
+    }
+
+    /*
+        The number of slots.
+
+        @return usize How many slots there are.
+    */
+    pub fn size(&self) -> usize {
+        self.slots.len()
+    }
+
+    /*
+        Looks into a slot.
+
+        @param index The slot.
+        @return Option<&Stack> The stack in the slot, if any.
+    */
+    pub fn slot(&self, index: usize) -> Option<&Stack> {
+        self.slots.get(index).and_then(|slot| slot.as_ref())
+    }
+
+    /*
+        Which slot is in hand.
+
+        @return usize The selected slot.
+    */
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    /*
+        Puts a slot in hand. Slots that don't exist are ignored.
+
+        @param index The slot to select.
+    */
+    pub fn select(&mut self, index: usize) {
+        if index < self.slots.len() {
+            self.selected = index;
+        }
+    }
+
+    /*
+        The item in hand.
+
+        @return Option<&Item> The top item of the selected stack, if any.
+    */
+    pub fn held(&self) -> Option<&Item> {
+        self.slot(self.selected).map(|stack| &stack.item)
+    }
+
+    /*
+        The weight of everything carried.
+
+        @return f64 The total weight.
+    */
+    pub fn weight(&self) -> f64 {
+        self.slots
+            .iter()
+            .flatten()
+            .map(|stack| stack.item.weight() * stack.count as f64)
+            .sum()
+    }
+
+    /*
+        The most weight that can be carried.
+
+        @return f64 The capacity.
+    */
+    pub fn capacity(&self) -> f64 {
+        self.capacity
+    }
+
+    /*
+        Counts the items of one kind, across every slot.
+
+        @param id The name of the item's prototype.
+        @return u32 How many are carried.
+    */
+    pub fn count(&self, id: &str) -> u32 {
+        self.slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.item.id == id)
+            .map(|stack| stack.count)
+            .sum()
+    }
+
+    /*
+        Whether nothing at all is carried.
+
+        @return bool Whether every slot is empty.
+    */
+    pub fn is_empty(&self) -> bool {
+        self.slots.iter().all(|slot| slot.is_none())
+    }
+
+    /*
+        Finds where an item would go: a stack of its kind with room to spare,
+        else the selected slot if empty, else the first empty slot.
+    */
+    fn slot_for(&self, item: &Item) -> Option<usize> {
+        let stackable = self.slots.iter().position(|slot| match *slot {
+            Some(ref stack) => stack.item.id == item.id && stack.count < item.max_stack(),
+            None => false,
+        });
+        stackable
+            .or_else(|| Some(self.selected).filter(|&i| self.slots[i].is_none()))
+            .or_else(|| self.slots.iter().position(|slot| slot.is_none()))
+    }
+
+    /*
+        Whether an item would fit, by both weight and slots.
+
+        @param item The item.
+        @return bool Whether there is room for it.
+    */
+    pub fn can_add(&self, item: &Item) -> bool {
+        self.weight() + item.weight() <= self.capacity && self.slot_for(item).is_some()
+    }
+
+    /*
+        Puts an item away, if there is room for it.
+
+        @param item The item.
+        @return bool Whether the item was added.
+    */
+    pub fn add(&mut self, item: Item) -> bool {
+        if !self.can_add(&item) {
+            return false;
+        }
+        let index = self.slot_for(&item).expect("checked for room");
+        match self.slots[index] {
+            Some(ref mut stack) => stack.count += 1,
+            None => self.slots[index] = Some(Stack { item, count: 1 }),
+        }
+        true
+    }
+
+    /*
+        Places a whole stack into a slot, replacing whatever was there.
+        Used to restore saved inventories.
+
+        @param index The slot.
+        @param stack The stack to put there.
+    */
+    pub fn set_slot(&mut self, index: usize, stack: Option<Stack>) {
+        if index < self.slots.len() {
+            self.slots[index] = stack;
+        }
+    }
+
+    /*
+        Takes one item out of the selected slot.
+
+        @return Option<Item> The item, if the slot held any.
+    */
+    pub fn take_held(&mut self) -> Option<Item> {
+        let index = self.selected;
+        self.take_from(index)
+    }
+
+    /*
+        Takes one item out of a slot, emptying it once the last is gone.
+    */
+    fn take_from(&mut self, index: usize) -> Option<Item> {
+        let item = {
+            let stack = self.slots.get_mut(index)?.as_mut()?;
+            stack.count -= 1;
+            stack.item.clone()
+        };
+        if self.slots[index].as_ref().is_some_and(|stack| stack.count == 0) {
+            self.slots[index] = None;
+        }
+        Some(item)
+    }
+
+    /*
+        Moves as much of a slot's stack into another inventory as will fit there.
+
+        @param index The slot to move from.
+        @param to The inventory to move into.
+        @return u32 How many items were moved.
+    */
+    pub fn move_stack(&mut self, index: usize, to: &mut Inventory) -> u32 {
+        let mut moved = 0;
+        while self.slot(index).is_some_and(|stack| to.can_add(&stack.item)) {
+            let item = self.take_from(index).expect("slot holds a stack");
+            to.add(item);
+            moved += 1;
+        }
+        moved
+    }
+
+    /*
+        Removes items of one kind, taking from any slot. Nothing is removed
+        unless there are enough.
+
+        @param id The name of the item's prototype.
+        @param count How many to remove.
+        @return bool Whether they were removed.
+    */
+    pub fn remove(&mut self, id: &str, count: u32) -> bool {
+        if self.count(id) < count {
+            return false;
+        }
+        for _ in 0..count {
+            let index = self.slots
+                .iter()
+                .position(|slot| slot.as_ref().is_some_and(|stack| stack.item.id == id))
+                .expect("counted enough");
+            self.take_from(index);
+        }
+        true
+    }
+
+    /*
+        Empties every slot.
+    */
+    pub fn clear(&mut self) {
+        for slot in &mut self.slots {
+            *slot = None;
+        }
+    }
+}
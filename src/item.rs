@@ -2,33 +2,70 @@
 //! Items have three different types - interactable, food, or resource.
 //! Resource items are used in crafting. Interactable items are used by the
 //! creature to fight, gather, etc. Food is to be eaten. Yum.
+//!
+//! Every item is defined in a data file in the data folder.
+//! Each definition begins with the item's id, followed by its properties:
+//!
+//! ```text
+//! # Comments and blank lines are ignored.
+//! item bisket
+//! category Food
+//! sprite bisket_item
+//! damage 1
+//! weight 1
+//! pickupable true
+//! stack 10
+//! effect eat 35 1
+//! ```
+//!
+//! The category and sprite are required. Otherwise, items do no damage, weigh 1,
+//! can be picked up, don't stack and have no use.
 
 use piston_window::*;
+use misc::DataError;
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
 use constants::*;
 
-#[derive(Clone)]
+pub const DEFAULT_ITEMS: &str = include_str!("../assets/data/items.txt");
+const THROW_FORCE: f64 = 40.0; // Divided by an item's weight to give its launch speed.
+const MAX_THROW_SPEED: f64 = 12.0;
+const FRICTION: f64 = 0.2; // Speed a sliding item loses each tick.
+const KIND: &str = "items"; // Names item files in errors.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ItemType {
-    Interactable(InteractableType), // Can be thrown or interacted with (intent: used for fighting / interacting with world).
-    Food(FoodType),                 // Can be thrown or consumed (intent: used for healing / buffs).
-    Resource(ResourceType),         // Can be thrown (intent: used for crafting).
-                                    // Other, // Can be thrown but provides passive effect (intent: used for passive effect).
+    Interactable, // Can be thrown or interacted with (intent: used for fighting / interacting with world).
+    Food,         // Can be thrown or consumed (intent: used for healing / buffs).
+    Resource,     // Can be thrown (intent: used for crafting).
+                  // Other, // Can be thrown but provides passive effect (intent: used for passive effect).
 }
 
-#[derive(Clone)]
-pub enum FoodType {
-    Bisket,
-}
+impl ItemType {
+    /*
+        Parses an item type from its name, as written by Debug.
 
-#[derive(Clone)]
-pub enum InteractableType {
-    Sword,
+        @param name The item type's name.
+        @return Option<ItemType> The item type, if the name is known.
+    */
+    pub fn from_name(name: &str) -> Option<ItemType> {
+        match name {
+            "Interactable" => Some(ItemType::Interactable),
+            "Food" => Some(ItemType::Food),
+            "Resource" => Some(ItemType::Resource),
+            _ => None,
+        }
+    }
 }
 
-#[derive(Clone)]
-pub enum ResourceType {
-    Logs,
-    Grune,
+// What happens when an item in hand is used.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UseEffect {
+    None,
+    Eat(f64, i32), // Eaten, restoring some food and health.
+    Swing,         // Swung as a weapon, dealing the item's damage.
 }
 
 #[derive(Clone)]
@@ -36,46 +73,60 @@ pub enum ResourceType {
 /**
     Implementation of the Item object.
 
+    @field id The name of the item's prototype.
     @field x Items's x position on map.
     @field y Item's y position on map.
     @field item_type The item type.
+    @field sprite The name of the item's texture.
     @field damage Damage done when item is thrown.
     @field pickupable Whether or not the item can be picked up.
     @field x_vel Velocity of item when being thrown.
     @field y_vel Velocity of item when being thrown.
     @field weight The item's weight.
+    @field max_stack How many of the item can share an inventory slot.
+    @field effect What using the item does.
+    @field on_ship Whether the item lies on the ship's deck, riding along with it.
 */
 pub struct Item {
+    pub id: String,
     pub x: f64,
     pub y: f64,
     pub item_type: ItemType,
+    sprite: String,
     damage: i32, // Damage done when thrown
     pickupable: bool,
     x_vel: f64,
     y_vel: f64,
     weight: f64,
+    max_stack: u32,
+    effect: UseEffect,
+    pub on_ship: bool,
 }
 
 impl Item {
     /*
-        Item constructor.
+        Item constructor, with the defaults for anything not given.
 
+        @param id The name of the item's prototype.
         @param item_type The item's type.
-        @param damage The amount of damage the item will do.
-        @param pickupable Whether the item can be picked up.
-        @param w The item's weight.
+        @param sprite The name of the item's texture.
         @return Item Returns self.
     */
-    pub fn new(item_type: ItemType, damage: i32, pickupable: bool, w: f64) -> Self {
+    pub fn new(id: &str, item_type: ItemType, sprite: &str) -> Self {
         Item {
+            id: id.to_string(),
             x: 0.0,
             y: 0.0,
             item_type: item_type,
-            damage: damage,
-            pickupable: pickupable,
+            sprite: sprite.to_string(),
+            damage: 0,
+            pickupable: true,
             x_vel: 0.0,
             y_vel: 0.0,
-            weight: w,
+            weight: 1.0,
+            max_stack: 1,
+            effect: UseEffect::None,
+            on_ship: false,
         }
     }
 
@@ -96,23 +147,124 @@ impl Item {
         trans_x: f64,
         trans_y: f64,
     ) {
-        match self.item_type {
-            ItemType::Food(FoodType::Bisket) => {
-                let img = IMG_ITEM_BISKET;
-                image(
-                    textures.get(img).expect(&format!("Not found: {:?}", img)),
-                    context
-                        .transform
-                        .trans(self.x, self.y)
-                        .trans(trans_x, trans_y)
-                        .scale(IMAGE_SCALE, IMAGE_SCALE),
-                    graphics,
-                );
-            }
-            _ => {}
+        let img = self.image();
+        image(
+            textures.get(img).expect(&format!("Not found: {:?}", img)),
+            context
+                .transform
+                .trans(self.x, self.y)
+                .trans(trans_x, trans_y)
+                .scale(IMAGE_SCALE, IMAGE_SCALE),
+            graphics,
+        );
+    }
+
+    /*
+        Finds the sprite for the item.
+
+        @return &str The item's texture name.
+    */
+    pub fn image(&self) -> &str {
+        &self.sprite
+    }
+
+    /*
+        The damage the item does when wielded or thrown.
+
+        @return i32 The item's damage.
+    */
+    pub fn damage(&self) -> i32 {
+        self.damage
+    }
+
+    /*
+        The item's weight, which counts against what a creature can carry.
+
+        @return f64 The item's weight.
+    */
+    pub fn weight(&self) -> f64 {
+        self.weight
+    }
+
+    /*
+        How many of the item can share an inventory slot.
+
+        @return u32 The stack limit for the item.
+    */
+    pub fn max_stack(&self) -> u32 {
+        self.max_stack
+    }
+
+    /*
+        Whether the item can be picked up.
+
+        @return bool Whether the item is pickupable.
+    */
+    pub fn is_pickupable(&self) -> bool {
+        self.pickupable
+    }
+
+    /*
+        What using the item does.
+
+        @return UseEffect The item's use effect.
+    */
+    pub fn effect(&self) -> UseEffect {
+        self.effect
+    }
+
+    /*
+        Launches the item. Heavier items fly slower.
+
+        @param dir_x The horizontal part of the direction thrown in.
+        @param dir_y The vertical part of the direction thrown in.
+    */
+    pub fn throw(&mut self, dir_x: f64, dir_y: f64) {
+        let speed = (THROW_FORCE / self.weight).min(MAX_THROW_SPEED);
+        self.x_vel = dir_x * speed;
+        self.y_vel = dir_y * speed;
+    }
+
+    /*
+        The item's velocity while flying.
+
+        @return (f64, f64) The horizontal and vertical velocity.
+    */
+    pub fn velocity(&self) -> (f64, f64) {
+        (self.x_vel, self.y_vel)
+    }
+
+    /*
+        Whether the item is still flying or sliding.
+
+        @return bool Whether the item is moving.
+    */
+    pub fn is_moving(&self) -> bool {
+        self.x_vel != 0.0 || self.y_vel != 0.0
+    }
+
+    /*
+        Slows the item down by friction, bringing it to rest eventually.
+    */
+    pub fn slide(&mut self) {
+        let speed = self.x_vel.hypot(self.y_vel);
+        if speed <= FRICTION {
+            self.stop();
+        } else {
+            let scale = (speed - FRICTION) / speed;
+            self.x_vel *= scale;
+            self.y_vel *= scale;
         }
     }
 
+    /*
+        Brings the item to rest.
+    */
+    pub fn stop(&mut self) {
+        self.x_vel = 0.0;
+        self.y_vel = 0.0;
+    }
+
     /*
         Generates a clone of the item using prototype.
         @param x The x value where the clone is placed.
@@ -127,3 +279,167 @@ impl Item {
         }
     }
 }
+
+/*
+    Creates prototypes of items for prototyping pattern, from the built-in definitions.
+
+    @return HashMap<String, Item> A map of string to items.
+*/
+pub fn generate_item_prototypes() -> HashMap<String, Item> {
+    parse_items(DEFAULT_ITEMS).expect("Error in default items")
+}
+
+/*
+    Parses item definitions into prototypes.
+
+    @param text The item file's contents.
+    @return Result<HashMap<String, Item>, DataError> The prototypes by id, or where parsing failed.
+*/
+pub fn parse_items(text: &str) -> Result<HashMap<String, Item>, DataError> {
+    let mut prototypes: HashMap<String, Item> = HashMap::new();
+    let mut current: Option<(usize, String)> = None; // The item being defined, and its line.
+    let mut fields: HashMap<&str, (usize, String)> = HashMap::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (keyword, rest) = match line.find(char::is_whitespace) {
+            Some(end) => (&line[..end], line[end..].trim()),
+            None => (line, ""),
+        };
+        match keyword {
+            "item" => {
+                if let Some((start, id)) = current.take() {
+                    let item = build_item(&id, &fields, start)?;
+                    prototypes.insert(id, item);
+                }
+                if rest.is_empty() {
+                    return Err(DataError::parse(KIND, line_number, "missing item id"));
+                }
+                if prototypes.contains_key(rest) {
+                    let message = format!("'{}' is already defined", rest);
+                    return Err(DataError::parse(KIND, line_number, &message));
+                }
+                current = Some((line_number, rest.to_string()));
+                fields.clear();
+            }
+            "category" | "sprite" | "damage" | "weight" | "pickupable" | "stack" | "effect" => {
+                if current.is_none() {
+                    let message = format!("'{}' before any item", keyword);
+                    return Err(DataError::parse(KIND, line_number, &message));
+                }
+                fields.insert(keyword, (line_number, rest.to_string()));
+            }
+            _ => {
+                let message = format!("unknown property '{}'", keyword);
+                return Err(DataError::parse(KIND, line_number, &message));
+            }
+        }
+    }
+    if let Some((start, id)) = current {
+        let item = build_item(&id, &fields, start)?;
+        prototypes.insert(id, item);
+    }
+    Ok(prototypes)
+}
+
+/*
+    Reads and parses an item definition file.
+
+    @param path The file's path.
+    @return Result<HashMap<String, Item>, DataError> The prototypes, or why loading failed.
+*/
+pub fn load_items<P: AsRef<Path>>(path: P) -> Result<HashMap<String, Item>, DataError> {
+    let text = fs::read_to_string(path)?;
+    parse_items(&text)
+}
+
+/*
+    Checks that every item the game refers to by id has a definition.
+
+    @param items The item prototypes.
+    @param ids The ids that must be defined.
+    @return Result<(), DataError> Ok, or the first id missing.
+*/
+pub fn check_required(items: &HashMap<String, Item>, ids: &[&str]) -> Result<(), DataError> {
+    match ids.iter().find(|id| !items.contains_key(**id)) {
+        Some(id) => Err(DataError::Missing {
+            kind: "item",
+            id: id.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/*
+    Builds an item from its properties, checking each one.
+*/
+fn build_item(
+    id: &str,
+    fields: &HashMap<&str, (usize, String)>,
+    start: usize,
+) -> Result<Item, DataError> {
+    let required = |name: &str| {
+        fields
+            .get(name)
+            .ok_or_else(|| DataError::parse(KIND, start, &format!("item '{}' has no {}", id, name)))
+    };
+    let (line, ref category) = *required("category")?;
+    let item_type = ItemType::from_name(category)
+        .ok_or_else(|| DataError::parse(KIND, line, &format!("unknown category '{}'", category)))?;
+    let (_, ref sprite) = *required("sprite")?;
+    let mut item = Item::new(id, item_type, sprite);
+    if let Some(damage) = value(fields, "damage")? {
+        item.damage = damage;
+    }
+    if let Some(weight) = value::<f64>(fields, "weight")? {
+        if weight <= 0.0 {
+            return Err(DataError::parse(KIND, fields["weight"].0, "weight must be positive"));
+        }
+        item.weight = weight;
+    }
+    if let Some(pickupable) = value(fields, "pickupable")? {
+        item.pickupable = pickupable;
+    }
+    if let Some(stack) = value::<u32>(fields, "stack")? {
+        if stack == 0 {
+            return Err(DataError::parse(KIND, fields["stack"].0, "stack must be at least 1"));
+        }
+        item.max_stack = stack;
+    }
+    if let Some(&(line, ref text)) = fields.get("effect") {
+        let mut words = text.split_whitespace();
+        item.effect = match (words.next(), words.next(), words.next()) {
+            (Some("none"), None, _) => UseEffect::None,
+            (Some("swing"), None, _) => UseEffect::Swing,
+            (Some("eat"), Some(food), heal) => match (food.parse(), heal.unwrap_or("0").parse()) {
+                (Ok(food), Ok(heal)) if words.next().is_none() => UseEffect::Eat(food, heal),
+                _ => {
+                    let message = format!("bad eat amounts '{}'", text);
+                    return Err(DataError::parse(KIND, line, &message));
+                }
+            },
+            _ => return Err(DataError::parse(KIND, line, &format!("unknown effect '{}'", text))),
+        };
+    }
+    Ok(item)
+}
+
+/*
+    Parses an optional property, if given.
+*/
+fn value<T: FromStr>(
+    fields: &HashMap<&str, (usize, String)>,
+    name: &str,
+) -> Result<Option<T>, DataError> {
+    match fields.get(name) {
+        Some(&(line, ref text)) => match text.parse() {
+            Ok(value) => Ok(Some(value)),
+            Err(_) => Err(DataError::parse(KIND, line, &format!("bad {} '{}'", name, text))),
+        },
+        None => Ok(None),
+    }
+}
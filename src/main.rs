@@ -17,7 +17,15 @@ mod ship;
 mod misc;
 mod map;
 mod item;
+mod save;
+mod blueprint;
+mod world;
 mod constants;
+mod timestep;
+mod ai;
+mod pathfinding;
+mod inventory;
+mod crafting;
 
 use piston_window::*;
 use game::Game;
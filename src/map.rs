@@ -1,88 +1,557 @@
 //! Map generates and manages the tileset for the map using Perlin and Worley generations.
 //! Draws the Map with proper tilesets based on generation.
+//!
+//! The world is infinite: tiles live in fixed-size chunks which are generated on demand
+//! around the player and evicted once far away. Tiles changed during play are kept
+//! separately so they survive a chunk being evicted and regenerated.
+//!
+//! Land only exists on floating islands. The world is split into square regions which
+//! hold at most one island each, with open sky between them.
 
 use noise::*;
 use rand::*;
+use rand::distributions::{IndependentSample, Normal};
 use tile::{Tile, TileType};
 use constants::*;
 use piston_window::*;
-use std::cmp;
 use std::collections::HashMap;
 
 const STEP_SIZE: f64 = 0.1;
 const BASE_WEIGHT: f64 = 0.2;
 const WATER_CHANGE_RATE: i32 = 10;
+const TEXTURE_CHANCE: f64 = 0.10;
+
+const ISLAND_REGION_SIZE: i32 = CHUNK_SIZE * 4; // Keeps every chunk inside one region.
+const ISLAND_CHANCE: f64 = 0.8;
+const ISLAND_MEAN: f64 = 60.0;
+const ISLAND_STANDARD_DEV: f64 = 16.0;
+const ISLAND_LOWERBOUND: f64 = 16.0;
+const ISLAND_UPPERBOUND: f64 = 112.0;
+const ISLAND_SALT: u64 = 0x1514_4E0D;
+const COAST_ROUGHNESS: f64 = 0.4;
+
+// Sprite, rotation and x/y shift to draw, keyed by which neighbours (Right, Down, Left, Up) match.
+type SpriteMap = HashMap<(bool, bool, bool, bool), (Option<String>, f64, f64, f64)>;
+
+/*
+    The seed a world is generated from. Every noise layer and texture roll
+    is drawn from one RNG built from this value, so equal seeds give equal maps.
+
+    @field 0 The raw seed value.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WorldSeed(pub u64);
+
+impl WorldSeed {
+    /*
+        Rolls a new seed from the thread RNG.
+
+        @return WorldSeed A random seed.
+    */
+    pub fn random() -> Self {
+        WorldSeed(thread_rng().gen::<u64>())
+    }
+
+    /*
+        Builds the generation RNG for this seed.
+
+        @return XorShiftRng A seeded RNG.
+    */
+    pub fn rng(&self) -> XorShiftRng {
+        let lo = self.0 as u32;
+        let hi = (self.0 >> 32) as u32;
+        // XorShift must never be seeded with all zeroes.
+        XorShiftRng::from_seed([lo, hi, lo ^ 0x9E37_79B9, hi ^ 0x7F4A_7C15])
+    }
+
+    /*
+        Derives the seed for a single chunk, so chunks can be generated in any order.
+
+        @param cx The chunk's x coordinate.
+        @param cy The chunk's y coordinate.
+        @return WorldSeed The chunk's seed.
+    */
+    pub fn for_chunk(&self, cx: i32, cy: i32) -> WorldSeed {
+        let cx = (cx as u32 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let cy = (cy as u32 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+        WorldSeed(self.0 ^ cx ^ cy.rotate_left(32))
+    }
+
+    /*
+        Derives the seed for a single island region.
+
+        @param rx The region's x coordinate.
+        @param ry The region's y coordinate.
+        @return WorldSeed The region's seed.
+    */
+    pub fn for_region(&self, rx: i32, ry: i32) -> WorldSeed {
+        WorldSeed(self.0 ^ ISLAND_SALT).for_chunk(rx, ry)
+    }
+}
+
+/*
+    A floating island, bounded by a square of tiles.
+
+    @field id Unique id of the island, derived from its region.
+    @field x The tile x coordinate of the island's left edge.
+    @field y The tile y coordinate of the island's top edge.
+    @field size The island's width and height in tiles.
+*/
+#[derive(Clone, Debug, PartialEq)]
+pub struct Island {
+    pub id: u64,
+    pub x: i32,
+    pub y: i32,
+    pub size: i32,
+}
+
+impl Island {
+    /*
+        Determines whether a tile lies within the island's bounds.
+
+        @param x The tile's x coordinate.
+        @param y The tile's y coordinate.
+        @return bool Whether the tile is in bounds.
+    */
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && x < self.x + self.size && y >= self.y && y < self.y + self.size
+    }
+
+    /*
+        Radial falloff mask: 1.0 around the middle of the island, fading to 0.0 at its rim.
+
+        @param x The tile's x coordinate.
+        @param y The tile's y coordinate.
+        @return f64 The mask weight.
+    */
+    fn falloff(&self, x: i32, y: i32) -> f64 {
+        let middle = (self.size as f64 - 1.0) / 2.0;
+        let dx = middle - (x - self.x) as f64;
+        let dy = middle - (y - self.y) as f64;
+        let val = (middle - (dx * dx + dy * dy).sqrt()) / middle;
+        (val * 1.5).clamp(0.0, 1.0)
+    }
+}
+
+/*
+    The noise layers used to decide the terrain of any tile in the world.
+
+    @field cells Worley noise shaping land masses.
+    @field elevation Perlin noise weighting the land masses.
+    @field moisture Perlin noise deciding where trees grow.
+*/
+struct Terrain {
+    cells: Worley,
+    elevation: Perlin,
+    moisture: Perlin,
+}
+
+impl Terrain {
+    /*
+        Terrain constructor. Noise layers are seeded in a fixed order from the world seed.
+
+        @param seed The world seed.
+        @return Terrain Returns itself.
+    */
+    fn new(seed: WorldSeed) -> Self {
+        let mut rng = seed.rng();
+        Terrain {
+            cells: Worley::new().set_seed(rng.gen::<u32>()).enable_range(true),
+            elevation: Perlin::new().set_seed(rng.gen::<u32>()),
+            moisture: Perlin::new().set_seed(rng.gen::<u32>()),
+        }
+    }
+
+    /*
+        Determines the terrain type at a tile position.
+
+        @param x The tile's x coordinate.
+        @param y The tile's y coordinate.
+        @return TileType The generated tile type.
+    */
+    fn tile_type_at(&self, x: i32, y: i32, island: Option<&Island>) -> TileType {
+        let island = match island {
+            Some(island) if island.contains(x, y) => island,
+            _ => return TileType::Air,
+        };
+        // Roughen the circular mask so coastlines aren't perfect circles.
+        let coast = sample(&self.elevation, x, y, STEP_SIZE * 2.0);
+        if island.falloff(x, y) - (1.0 - coast) * COAST_ROUGHNESS <= 0.0 {
+            return TileType::Air;
+        }
+
+        let cells = sample(&self.cells, x, y, STEP_SIZE / 2.0);
+        let elevation = (sample(&self.elevation, x, y, STEP_SIZE) + BASE_WEIGHT).min(1.0);
+        let moist = sample(&self.moisture, x, y, STEP_SIZE);
+
+        let num = cells * elevation;
+        if num <= 0.1 {
+            TileType::Water
+        } else if num <= 0.2 {
+            TileType::DirtFloor
+        } else if num <= 0.6 {
+            if moist >= 0.7 {
+                TileType::Tree
+            } else {
+                TileType::GrassFloor
+            }
+        } else {
+            TileType::StoneWall
+        }
+    }
+}
+
+/*
+    A square block of CHUNK_SIZE x CHUNK_SIZE tiles, indexed [x][y] locally.
+
+    @field tiles The chunk's tiles.
+*/
+pub struct Chunk {
+    pub tiles: Vec<Vec<Tile>>,
+}
 
 /*
     Implementation of the Map object.
 
-    @field tiles A 2D vector of all the tiles in the map.
+    @field seed The seed the map was generated from.
+    @field chunks The currently loaded chunks, keyed by chunk coordinates.
+    @field edits Tiles changed since generation, keyed by tile coordinates.
+    @field regrowth Harvested tiles growing back, with the ticks left, keyed by tile coordinates.
+    @field beneath The ground under each structure the player has built, keyed by tile coordinates.
+    @field terrain The noise layers used for generation.
+    @field islands Every island generated so far, keyed by id.
     @field grass_dirt_map A HashMap used for drawing grass graphics.
     @field stone_map A HashMap used for drawing stone graphics.
+    @field wood_wall_map A HashMap used for drawing wooden wall graphics.
     @field under_portal The tile under the portal.
     @field frames_since_last_draw Used for water animation.
 */
 pub struct Map {
-    pub tiles: Vec<Vec<Tile>>,
-    grass_dirt_map: HashMap<(bool, bool, bool, bool), (Option<String>, f64, f64, f64)>,
-    stone_map: HashMap<(bool, bool, bool, bool), (Option<String>, f64, f64, f64)>,
+    pub seed: WorldSeed,
+    chunks: HashMap<(i32, i32), Chunk>,
+    edits: HashMap<(i32, i32), Tile>,
+    regrowth: HashMap<(i32, i32), (TileType, i32)>,
+    beneath: HashMap<(i32, i32), Tile>,
+    terrain: Terrain,
+    islands: HashMap<u64, Island>,
+    grass_dirt_map: SpriteMap,
+    stone_map: SpriteMap,
+    wood_wall_map: SpriteMap,
     pub under_portal: Tile,
     frames_since_last_draw: i32,
 }
 
 impl Map {
     /*
-        Map constructor.
+        Map constructor using a given seed. No chunks are generated until requested.
 
-        @param width The width of the map.
-        @param height The height of the map.
+        @param seed The seed driving all generation.
         @return Map Returns itself.
     */
-    pub fn new(width: usize, height: usize) -> Self {
-        // Easy reference to tile types.
-        let air = Tile::new(TileType::Air);
-        let grass_floor = Tile::new(TileType::GrassFloor);
-        let water = Tile::new(TileType::Water);
-        let dirt_floor = Tile::new(TileType::DirtFloor);
-        let stone_wall = Tile::new(TileType::StoneWall);
-        let tree = Tile::new(TileType::Tree);
-
-        let mut map_tiles = vec![vec![air.clone(); height]; width];
-        let worley_arr = generate_worley(width, height, STEP_SIZE / 2.0);
-        let perlin_arr = generate_perlin(width, height, STEP_SIZE);
-        let perlin_arr = add_base_weight(&perlin_arr, BASE_WEIGHT);
-        let moist = generate_perlin(width, height, STEP_SIZE);
-
-        // Create the 2D vector of TileTypes based on random generation above.
-        for i in 0..map_tiles.len() {
-            for j in 0..map_tiles[i].len() {
-                let num = worley_arr[i][j] * perlin_arr[i][j];
-                if num <= 0.1 {
-                    map_tiles[i][j] = water.clone();
-                } else if num <= 0.2 {
-                    map_tiles[i][j] = dirt_floor.clone();
-                } else if num <= 0.6 {
-                    if moist[i][j] >= 0.7 {
-                        map_tiles[i][j] = tree.clone();
-                    } else {
-                        map_tiles[i][j] = grass_floor.clone();
-                    }
-                } else {
-                    map_tiles[i][j] = stone_wall.clone();
-                }
+    pub fn with_seed(seed: WorldSeed) -> Self {
+        Map {
+            seed,
+            chunks: HashMap::new(),
+            edits: HashMap::new(),
+            regrowth: HashMap::new(),
+            beneath: HashMap::new(),
+            terrain: Terrain::new(seed),
+            islands: HashMap::new(),
+            grass_dirt_map: populate_grass_dirt_map(),
+            stone_map: populate_stone_map(),
+            wood_wall_map: populate_wood_wall_map(),
+            under_portal: Tile::new(TileType::Air),
+            frames_since_last_draw: 0,
+        }
+    }
+
+    /*
+        Loads every chunk within CHUNK_LOAD_RADIUS of a world position and
+        evicts those beyond CHUNK_UNLOAD_RADIUS.
+
+        @param x The x position in pixels.
+        @param y The y position in pixels.
+    */
+    pub fn load_around(&mut self, x: f64, y: f64) {
+        let (center_x, center_y) = chunk_coords(tile_coord(x), tile_coord(y));
+        for cx in center_x - CHUNK_LOAD_RADIUS..center_x + CHUNK_LOAD_RADIUS + 1 {
+            for cy in center_y - CHUNK_LOAD_RADIUS..center_y + CHUNK_LOAD_RADIUS + 1 {
+                self.load_chunk(cx, cy);
+            }
+        }
+        self.chunks.retain(|&(cx, cy), _| {
+            (cx - center_x).abs() <= CHUNK_UNLOAD_RADIUS
+                && (cy - center_y).abs() <= CHUNK_UNLOAD_RADIUS
+        });
+    }
 
-                if random::<f64>() < 0.10 {
-                    map_tiles[i][j].texture = true;
+    /*
+        Generates a chunk if it isn't already loaded.
+
+        @param cx The chunk's x coordinate.
+        @param cy The chunk's y coordinate.
+    */
+    pub fn load_chunk(&mut self, cx: i32, cy: i32) {
+        if self.chunks.contains_key(&(cx, cy)) {
+            return;
+        }
+        let (rx, ry) = (
+            div_floor(cx * CHUNK_SIZE, ISLAND_REGION_SIZE),
+            div_floor(cy * CHUNK_SIZE, ISLAND_REGION_SIZE),
+        );
+        let island = generate_island(self.seed, rx, ry);
+        let chunk = self.generate_chunk(cx, cy, island.as_ref());
+        if let Some(island) = island {
+            self.islands.insert(island.id, island);
+        }
+        self.chunks.insert((cx, cy), chunk);
+    }
+
+    /*
+        Finds the island a tile belongs to, if that island has been generated.
+
+        @param x The tile's x coordinate.
+        @param y The tile's y coordinate.
+        @return Option<&Island> The island whose bounds hold the tile.
+    */
+    pub fn island_at(&self, x: i32, y: i32) -> Option<&Island> {
+        let id = island_id(div_floor(x, ISLAND_REGION_SIZE), div_floor(y, ISLAND_REGION_SIZE));
+        self.islands
+            .get(&id)
+            .filter(|island| island.contains(x, y))
+    }
+
+    /*
+        Generates the tiles of a chunk from the seed, then reapplies any edits inside it.
+
+        @param cx The chunk's x coordinate.
+        @param cy The chunk's y coordinate.
+        @param island The island of the chunk's region, if any.
+        @return Chunk The generated chunk.
+    */
+    fn generate_chunk(&self, cx: i32, cy: i32, island: Option<&Island>) -> Chunk {
+        let mut rng = self.seed.for_chunk(cx, cy).rng();
+        let size = CHUNK_SIZE as usize;
+        let mut tiles = vec![vec![Tile::new(TileType::Air); size]; size];
+
+        for (i, column) in tiles.iter_mut().enumerate() {
+            for (j, tile) in column.iter_mut().enumerate() {
+                let x = cx * CHUNK_SIZE + i as i32;
+                let y = cy * CHUNK_SIZE + j as i32;
+                *tile = Tile::new(self.terrain.tile_type_at(x, y, island));
+                if rng.gen::<f64>() < TEXTURE_CHANCE {
+                    tile.texture = true;
+                }
+                if let Some(edit) = self.edits.get(&(x, y)) {
+                    *tile = edit.clone();
                 }
             }
         }
+        Chunk { tiles }
+    }
 
-        Map {
-            tiles: map_tiles,
-            grass_dirt_map: populate_grass_dirt_map(),
-            stone_map: populate_stone_map(),
-            under_portal: air,
-            frames_since_last_draw: 0,
+    /*
+        Retrieves the tile at a tile position, if its chunk is loaded.
+
+        @param x The tile's x coordinate.
+        @param y The tile's y coordinate.
+        @return Option<&Tile> The tile.
+    */
+    pub fn tile(&self, x: i32, y: i32) -> Option<&Tile> {
+        let (cx, cy) = chunk_coords(x, y);
+        self.chunks.get(&(cx, cy)).map(|chunk| {
+            &chunk.tiles[(x - cx * CHUNK_SIZE) as usize][(y - cy * CHUNK_SIZE) as usize]
+        })
+    }
+
+    /*
+        Retrieves the tile at a tile position mutably, if its chunk is loaded.
+        Changes made through this are not remembered after eviction; use set_tile for that.
+
+        @param x The tile's x coordinate.
+        @param y The tile's y coordinate.
+        @return Option<&mut Tile> The tile.
+    */
+    fn tile_mut(&mut self, x: i32, y: i32) -> Option<&mut Tile> {
+        let (cx, cy) = chunk_coords(x, y);
+        self.chunks.get_mut(&(cx, cy)).map(|chunk| {
+            &mut chunk.tiles[(x - cx * CHUNK_SIZE) as usize][(y - cy * CHUNK_SIZE) as usize]
+        })
+    }
+
+    /*
+        Retrieves the tile under a world position, if its chunk is loaded.
+
+        @param x The x position in pixels.
+        @param y The y position in pixels.
+        @return Option<&Tile> The tile.
+    */
+    pub fn tile_at_position(&self, x: f64, y: f64) -> Option<&Tile> {
+        self.tile(tile_coord(x), tile_coord(y))
+    }
+
+    /*
+        Replaces a tile, remembering the change so it survives chunk eviction.
+
+        @param x The tile's x coordinate.
+        @param y The tile's y coordinate.
+        @param tile The new tile.
+    */
+    pub fn set_tile(&mut self, x: i32, y: i32, tile: Tile) {
+        if let Some(t) = self.tile_mut(x, y) {
+            *t = tile.clone();
+        }
+        self.edits.insert((x, y), tile);
+    }
+
+    /*
+        Retrieves every tile changed since generation.
+
+        @return &HashMap<(i32, i32), Tile> Changed tiles keyed by tile coordinates.
+    */
+    pub fn edits(&self) -> &HashMap<(i32, i32), Tile> {
+        &self.edits
+    }
+
+    /*
+        Hits a harvestable tile, wearing down its health. Once worn away, the tile is
+        replaced by what harvesting leaves behind, and set to grow back if it does.
+
+        @param x The tile's x coordinate.
+        @param y The tile's y coordinate.
+        @param damage How much health the hit takes.
+        @return Option<&[&str]> The ids of the items dropped, if the tile was harvested.
+    */
+    pub fn hit_tile(&mut self, x: i32, y: i32, damage: i32) -> Option<&'static [&'static str]> {
+        let mut tile = self.tile(x, y)?.clone();
+        let (left, drops) = tile.tile_type.harvest()?;
+        tile.health -= damage;
+        if tile.health > 0 {
+            self.set_tile(x, y, tile);
+            return None;
+        }
+        if let Some(ticks) = tile.tile_type.regrowth_ticks() {
+            self.regrowth.insert((x, y), (tile.tile_type.clone(), ticks));
+        }
+        self.set_tile(x, y, Tile::new(left));
+        Some(drops)
+    }
+
+    /*
+        Counts down the harvested tiles growing back, and regrows those that are due.
+        A tile waits while something is in the way.
+
+        @param occupied Whether something stands on a tile, given its coordinates.
+    */
+    pub fn update_regrowth<F: Fn(i32, i32) -> bool>(&mut self, occupied: F) {
+        let mut due = vec![];
+        for (&(x, y), entry) in self.regrowth.iter_mut() {
+            entry.1 = (entry.1 - 1).max(0);
+            if entry.1 == 0 && !occupied(x, y) {
+                due.push((x, y));
+            }
+        }
+        for (x, y) in due {
+            let (tile_type, _) = self.regrowth.remove(&(x, y)).expect("regrowth is due");
+            self.set_tile(x, y, Tile::new(tile_type));
+        }
+    }
+
+    /*
+        Retrieves every harvested tile waiting to grow back.
+
+        @return &HashMap<(i32, i32), (TileType, i32)> What each tile grows back into,
+        and the ticks left, keyed by tile coordinates.
+    */
+    pub fn regrowth(&self) -> &HashMap<(i32, i32), (TileType, i32)> {
+        &self.regrowth
+    }
+
+    /*
+        Sets a tile to grow back after a while, as when restoring a save.
+
+        @param x The tile's x coordinate.
+        @param y The tile's y coordinate.
+        @param tile_type What the tile grows back into.
+        @param ticks The ticks until it does.
+    */
+    pub fn set_regrowth(&mut self, x: i32, y: i32, tile_type: TileType, ticks: i32) {
+        self.regrowth.insert((x, y), (tile_type, ticks));
+    }
+
+    /*
+        Builds a structure over a tile, remembering the ground beneath so tearing the
+        structure down restores it. Anything growing back there is lost.
+
+        @param x The tile's x coordinate.
+        @param y The tile's y coordinate.
+        @param tile The structure.
+    */
+    pub fn place_structure(&mut self, x: i32, y: i32, tile: Tile) {
+        if let Some(ground) = self.tile(x, y).cloned() {
+            self.beneath.insert((x, y), ground);
+            self.regrowth.remove(&(x, y));
+            self.set_tile(x, y, tile);
+        }
+    }
+
+    /*
+        Tears down a structure, putting back the ground that was beneath it.
+
+        @param x The tile's x coordinate.
+        @param y The tile's y coordinate.
+        @return Option<TileType> The structure's type, if one stood there.
+    */
+    pub fn remove_structure(&mut self, x: i32, y: i32) -> Option<TileType> {
+        let built = self.tile(x, y)?.tile_type.clone();
+        let ground = self.beneath.remove(&(x, y))?;
+        self.set_tile(x, y, ground);
+        Some(built)
+    }
+
+    /*
+        Whether the player has built a structure on a tile.
+
+        @param x The tile's x coordinate.
+        @param y The tile's y coordinate.
+        @return bool Whether the tile is a structure.
+    */
+    pub fn is_structure(&self, x: i32, y: i32) -> bool {
+        self.beneath.contains_key(&(x, y))
+    }
+
+    /*
+        Retrieves the ground under every structure the player has built.
+
+        @return &HashMap<(i32, i32), Tile> The ground tiles keyed by tile coordinates.
+    */
+    pub fn beneath(&self) -> &HashMap<(i32, i32), Tile> {
+        &self.beneath
+    }
+
+    /*
+        Records the ground under a structure, as when restoring a save.
+
+        @param x The tile's x coordinate.
+        @param y The tile's y coordinate.
+        @param ground The ground tile.
+    */
+    pub fn set_beneath(&mut self, x: i32, y: i32, ground: Tile) {
+        self.beneath.insert((x, y), ground);
+    }
+
+    /*
+        Determines whether the tile at a position is of a given type.
+
+        @param x The tile's x coordinate.
+        @param y The tile's y coordinate.
+        @param tile_type The type to compare against.
+        @return bool Whether the tile is loaded and matches.
+    */
+    pub fn is_tile_type(&self, x: i32, y: i32, tile_type: &TileType) -> bool {
+        match self.tile(x, y) {
+            Some(tile) => tile.tile_type == *tile_type,
+            None => false,
         }
     }
 
@@ -111,21 +580,13 @@ impl Map {
         trans_x: f64,
         trans_y: f64,
     ) {
-        let draw_start_i = ((player_x - w_width / 2.0) - IMAGE_SIZE_SCALED) / IMAGE_SIZE_SCALED;
-        let draw_start_j = ((player_y - w_height / 2.0) - IMAGE_SIZE_SCALED) / IMAGE_SIZE_SCALED;
-        let draw_start_i = cmp::max(0, draw_start_i as i32) as usize;
-        let draw_start_j = cmp::max(0, draw_start_j as i32) as usize;
-
-        for i in draw_start_i..self.tiles.len() {
-            if i as f64 * IMAGE_SIZE_SCALED > player_x + w_width / 2.0 {
-                // Off screen.
-                break;
-            }
-            for j in draw_start_j..self.tiles[i].len() {
-                if j as f64 * IMAGE_SIZE_SCALED > player_y + w_height / 2.0 {
-                    // Off screen.
-                    break;
-                }
+        let draw_start_i = tile_coord((player_x - w_width / 2.0) - IMAGE_SIZE_SCALED);
+        let draw_start_j = tile_coord((player_y - w_height / 2.0) - IMAGE_SIZE_SCALED);
+        let draw_end_i = tile_coord(player_x + w_width / 2.0);
+        let draw_end_j = tile_coord(player_y + w_height / 2.0);
+
+        for i in draw_start_i..draw_end_i + 1 {
+            for j in draw_start_j..draw_end_j + 1 {
                 // Retrieve set of information on what tile to draw, and its rotation/translation.
                 if let (Some(img), rot, shift_x, shift_y) = self.what_to_draw(i, j) {
                     if img == IMG_TREE {
@@ -150,6 +611,22 @@ impl Map {
                                 graphics,
                             );
                         }
+                    } else if let Some(ground) = self.beneath
+                        .get(&(i, j))
+                        .and_then(|tile| what_to_draw_tile(&tile.tile_type))
+                    {
+                        // Draw the ground under structures the player has built.
+                        image(
+                            textures
+                                .get(&ground)
+                                .unwrap_or_else(|| panic!("Not found: {:?}", ground)),
+                            context
+                                .transform
+                                .trans(i as f64 * IMAGE_SIZE_SCALED, j as f64 * IMAGE_SIZE_SCALED)
+                                .trans(trans_x, trans_y)
+                                .scale(IMAGE_SCALE, IMAGE_SCALE),
+                            graphics,
+                        );
                     } else if img == IMG_PORTAL {
                         let under_portal_image = what_to_draw_tile(&self.under_portal.tile_type);
                         if let Some(p_img) = under_portal_image {
@@ -194,19 +671,27 @@ impl Map {
         @param y The y location.
         @return A tuple containing the image string for the textures map, the rotation degree, x translation, and y translation.
     */
-    fn what_to_draw(&mut self, x: usize, y: usize) -> (Option<String>, f64, f64, f64) {
+    fn what_to_draw(&mut self, x: i32, y: i32) -> (Option<String>, f64, f64, f64) {
         let img;
         let rot = 0.0;
         let shift_x = 0.0;
         let shift_y = 0.0;
-        match self.tiles[x][y].tile_type {
+        let tile_type = match self.tile(x, y) {
+            Some(tile) => tile.tile_type.clone(),
+            None => return (None, rot, shift_x, shift_y), // Chunk not loaded.
+        };
+        match tile_type {
             TileType::Water => {
-                if self.frames_since_last_draw > WATER_CHANGE_RATE {
-                    self.tiles[x][y].texture = random();
+                let reroll = self.frames_since_last_draw > WATER_CHANGE_RATE;
+                if reroll {
                     self.frames_since_last_draw = 0;
                 }
                 self.frames_since_last_draw += 1;
-                match self.tiles[x][y].texture {
+                let tile = self.tile_mut(x, y).expect("water tile unloaded");
+                if reroll {
+                    tile.texture = random();
+                }
+                match tile.texture {
                     false => img = Some(IMG_WATER.to_string()),
                     true => img = Some(IMG_WATER_TEXTURE.to_string()),
                 }
@@ -215,31 +700,11 @@ impl Map {
             // TODO Separate into its own function.
             // Determines the correct stone tile from the stone HashMap.
             TileType::StoneWall => {
-                let mut up = false;
-                let mut left = false;
-                let mut right = false;
-                let mut down = false;
-
-                if x > 0 {
-                    if self.tiles[x - 1][y].tile_type == TileType::StoneWall {
-                        left = true;
-                    }
-                }
-                if x < self.tiles.len() - 1 {
-                    if self.tiles[x + 1][y].tile_type == TileType::StoneWall {
-                        right = true;
-                    }
-                }
-                if y > 0 {
-                    if self.tiles[x][y - 1].tile_type == TileType::StoneWall {
-                        up = true;
-                    }
-                }
-                if y < self.tiles[x].len() - 1 {
-                    if self.tiles[x][y + 1].tile_type == TileType::StoneWall {
-                        down = true;
-                    }
-                }
+                let wall = TileType::StoneWall;
+                let left = self.is_tile_type(x - 1, y, &wall);
+                let right = self.is_tile_type(x + 1, y, &wall);
+                let up = self.is_tile_type(x, y - 1, &wall);
+                let down = self.is_tile_type(x, y + 1, &wall);
 
                 // Retrieve from map.
                 let key = (right, down, left, up);
@@ -251,7 +716,7 @@ impl Map {
                             .clone();
                         return temp;
                     } else {
-                        match self.tiles[x][y].texture {
+                        match self.tile(x, y).is_some_and(|t| t.texture) {
                             false => img = Some(IMG_STONE_WALL.to_string()),
                             true => img = Some(IMG_STONE_WALL_TEXTURE.to_string()),
                         }
@@ -266,7 +731,7 @@ impl Map {
                 return self.get_grass_tile_info(x, y);
             }
 
-            TileType::DirtFloor => match self.tiles[x][y].texture {
+            TileType::DirtFloor => match self.tile(x, y).is_some_and(|t| t.texture) {
                 false => img = Some(IMG_DIRT_FLOOR.to_string()),
                 true => img = Some(IMG_DIRT_FLOOR_TEXTURE.to_string()),
             },
@@ -276,6 +741,19 @@ impl Map {
             TileType::Portal => {
                 img = Some(IMG_PORTAL.to_string());
             }
+
+            // Walls join up with the walls and doors beside them.
+            TileType::WoodWall => {
+                let joins = |x, y| {
+                    self.is_tile_type(x, y, &TileType::WoodWall)
+                        || self.is_tile_type(x, y, &TileType::Door)
+                };
+                let key = (joins(x + 1, y), joins(x, y + 1), joins(x - 1, y), joins(x, y - 1));
+                return self.wood_wall_map[&key].clone();
+            }
+            TileType::WoodFloor | TileType::Door | TileType::Chest | TileType::Workbench => {
+                img = structure_image(&tile_type).map(|i| i.to_string());
+            }
             _ => img = None,
         }
 
@@ -290,32 +768,12 @@ impl Map {
         @param y The y position of the tile.
         @return A tuple containing the image string for the textures map, the rotation degree, x translation, and y translation.
     */
-    fn get_grass_tile_info(&self, x: usize, y: usize) -> (Option<String>, f64, f64, f64) {
-        let mut up = false;
-        let mut left = false;
-        let mut right = false;
-        let mut down = false;
-
-        if x > 0 {
-            if self.tiles[x - 1][y].tile_type == TileType::DirtFloor {
-                left = true;
-            }
-        }
-        if x < self.tiles.len() - 1 {
-            if self.tiles[x + 1][y].tile_type == TileType::DirtFloor {
-                right = true;
-            }
-        }
-        if y > 0 {
-            if self.tiles[x][y - 1].tile_type == TileType::DirtFloor {
-                up = true;
-            }
-        }
-        if y < self.tiles[x].len() - 1 {
-            if self.tiles[x][y + 1].tile_type == TileType::DirtFloor {
-                down = true;
-            }
-        }
+    fn get_grass_tile_info(&self, x: i32, y: i32) -> (Option<String>, f64, f64, f64) {
+        let dirt = TileType::DirtFloor;
+        let left = self.is_tile_type(x - 1, y, &dirt);
+        let right = self.is_tile_type(x + 1, y, &dirt);
+        let up = self.is_tile_type(x, y - 1, &dirt);
+        let down = self.is_tile_type(x, y + 1, &dirt);
 
         // Retrieve from HashMap.
         let key = (right, down, left, up);
@@ -328,7 +786,7 @@ impl Map {
                     .clone();
                 return grass_tile;
             } else {
-                match self.tiles[x][y].texture {
+                match self.tile(x, y).is_some_and(|t| t.texture) {
                     false => img = Some(IMG_GRASS_FLOOR.to_string()),
                     true => img = Some(IMG_GRASS_FLOOR_TEXTURE.to_string()),
                 }
@@ -342,71 +800,99 @@ impl Map {
 }
 
 /*
-    Generates perlin noise to be used in procedural map gen.
+    Converts a world position in pixels to a tile coordinate.
 
-    @param width The width of the map.
-    @param height The height of the map.
-    @param step Step value of the noise.
-    @return Vec<Vec<f64>> Used for generating map.
+    @param position The x or y position in pixels.
+    @return i32 The tile coordinate containing it.
 */
-fn generate_perlin(width: usize, height: usize, step: f64) -> Vec<Vec<f64>> {
-    let mut rng = thread_rng();
-    let noise = Perlin::new().set_seed(rng.gen::<u32>());
-    let mut xpos = 0.0;
-    let mut ypos = 0.0;
-    let mut arr = vec![vec![0.0; height]; width];
-    for i in 0..width {
-        for j in 0..height {
-            arr[i][j] = (noise.get([xpos, ypos]) + 1.0) / 2.0;
-            xpos += step;
-        }
-        xpos = 0.0;
-        ypos += step;
+pub fn tile_coord(position: f64) -> i32 {
+    (position / IMAGE_SIZE_SCALED).floor() as i32
+}
+
+/*
+    Determines which chunk a tile belongs to.
+
+    @param x The tile's x coordinate.
+    @param y The tile's y coordinate.
+    @return (i32, i32) The chunk coordinates.
+*/
+pub fn chunk_coords(x: i32, y: i32) -> (i32, i32) {
+    (div_floor(x, CHUNK_SIZE), div_floor(y, CHUNK_SIZE))
+}
+
+/*
+    Integer division rounding towards negative infinity, so negative tiles map to negative chunks.
+*/
+fn div_floor(a: i32, b: i32) -> i32 {
+    let d = a / b;
+    if a % b != 0 && (a < 0) != (b < 0) {
+        d - 1
+    } else {
+        d
     }
-    arr
 }
 
 /*
-    Adds a base weight to 2D map vector.
+    Packs region coordinates into an island id.
 
-    @param arr The current 2D vector.
-    @return Vec<Vec<f64>> The updated 2D vector.
+    @param rx The region's x coordinate.
+    @param ry The region's y coordinate.
+    @return u64 The id.
 */
-fn add_base_weight(arr: &Vec<Vec<f64>>, base: f64) -> Vec<Vec<f64>> {
-    let mut weighted = vec![vec![0.0; arr[0].len()]; arr.len()];
-    for i in 0..arr.len() {
-        for j in 0..arr[i].len() {
-            let temp = arr[i][j] + base;
-            let temp = temp.min(1.0);
-            weighted[i][j] = temp;
-        }
+fn island_id(rx: i32, ry: i32) -> u64 {
+    ((rx as u32 as u64) << 32) | ry as u32 as u64
+}
+
+/*
+    Places the island of a region, if the region has one. Islands always fit inside
+    their region, leaving open sky towards the neighbouring regions.
+
+    @param seed The world seed.
+    @param rx The region's x coordinate.
+    @param ry The region's y coordinate.
+    @return Option<Island> The region's island.
+*/
+fn generate_island(seed: WorldSeed, rx: i32, ry: i32) -> Option<Island> {
+    let mut rng = seed.for_region(rx, ry).rng();
+    if rng.gen::<f64>() >= ISLAND_CHANCE {
+        return None;
     }
-    weighted
+    let size = generate_island_size(&mut rng);
+    let slack = ISLAND_REGION_SIZE - size;
+    Some(Island {
+        id: island_id(rx, ry),
+        x: rx * ISLAND_REGION_SIZE + rng.gen_range(0, slack + 1),
+        y: ry * ISLAND_REGION_SIZE + rng.gen_range(0, slack + 1),
+        size,
+    })
 }
 
 /*
-    Generates worley noise to be used in procedural map gen.
+    Rolls a normally distributed island size, retrying until it is within bounds.
 
-    @param width The width of the map.
-    @param height The height of the map.
-    @param step Step value of the noise.
-    @return Vec<Vec<f64>> Used for generating map.
+    @param rng The region's RNG.
+    @return i32 The island's width and height in tiles.
 */
-fn generate_worley(width: usize, height: usize, step: f64) -> Vec<Vec<f64>> {
-    let mut rng = thread_rng();
-    let noise = Worley::new().set_seed(rng.gen::<u32>()).enable_range(true);
-    let mut xpos = 0.0;
-    let mut ypos = 0.0;
-    let mut arr = vec![vec![0.0; height]; width];
-    for i in 0..width {
-        for j in 0..height {
-            arr[i][j] = (noise.get([xpos, ypos]) + 1.0) / 2.0;
-            xpos += step;
-        }
-        xpos = 0.0;
-        ypos += step;
+fn generate_island_size<R: Rng>(rng: &mut R) -> i32 {
+    let normal = Normal::new(ISLAND_MEAN, ISLAND_STANDARD_DEV);
+    let mut island_size = normal.ind_sample(rng);
+    while !(ISLAND_LOWERBOUND..=ISLAND_UPPERBOUND).contains(&island_size) {
+        island_size = normal.ind_sample(rng);
     }
-    arr
+    island_size as i32
+}
+
+/*
+    Samples a noise layer at a tile position, normalized to [0, 1].
+
+    @param noise The noise layer.
+    @param x The tile's x coordinate.
+    @param y The tile's y coordinate.
+    @param step Step value of the noise.
+    @return f64 The sampled value.
+*/
+fn sample<N: NoiseFn<[f64; 2]>>(noise: &N, x: i32, y: i32, step: f64) -> f64 {
+    (noise.get([y as f64 * step, x as f64 * step]) + 1.0) / 2.0
 }
 
 /*
@@ -648,6 +1134,64 @@ fn populate_stone_map() -> HashMap<(bool, bool, bool, bool), (Option<String>, f6
     s_map
 }
 
+/*
+    Creates a HashMap to easily access different tile sprites for complex tile sets.
+    Specific to wooden walls. Each sprite is drawn joining to the right, and rotated
+    clockwise in quarter turns to cover the other ways a wall can join up.
+
+    @return HashMap<(bool, bool, bool, bool), (Option<String>, f64, f64, f64)>
+    A HashMap of tuples representing surrounding walls (Right, Down, Left, Up)
+    mapped to a tuple of specifications for drawing an tile (see draw above).
+*/
+fn populate_wood_wall_map() -> SpriteMap {
+    let mut w_map = HashMap::new();
+
+    // Right, Down, Left, Up
+    let sprites = [
+        ([false, false, false, false], IMG_WOOD_WALL_POST),
+        ([true, false, false, false], IMG_WOOD_WALL_END),
+        ([true, false, true, false], IMG_WOOD_WALL_STRAIGHT),
+        ([true, true, false, false], IMG_WOOD_WALL_CORNER),
+        ([true, true, true, false], IMG_WOOD_WALL_TEE),
+        ([true, true, true, true], IMG_WOOD_WALL_CROSS),
+    ];
+    // Rotating about the top left corner swings the sprite off its tile, so shift it back.
+    let turns = [
+        (0.0, 0.0, 0.0),
+        (90.0, IMAGE_SIZE_SCALED, 0.0),
+        (180.0, IMAGE_SIZE_SCALED, IMAGE_SIZE_SCALED),
+        (270.0, 0.0, IMAGE_SIZE_SCALED),
+    ];
+    for &(sides, img) in &sprites {
+        for (turn, &(rot, shift_x, shift_y)) in turns.iter().enumerate() {
+            let side = |i: usize| sides[(i + 4 - turn) % 4];
+            let key = (side(0), side(1), side(2), side(3));
+            w_map
+                .entry(key)
+                .or_insert_with(|| (Some(img.to_string()), rot, shift_x, shift_y));
+        }
+    }
+
+    w_map
+}
+
+/*
+    Determines the sprite of a structure the player can build, as it looks on its own.
+
+    @param tile_type The structure's type.
+    @return Option<&str> The sprite, if the tile type is a structure.
+*/
+pub fn structure_image(tile_type: &TileType) -> Option<&'static str> {
+    match *tile_type {
+        TileType::WoodWall => Some(IMG_WOOD_WALL_POST),
+        TileType::WoodFloor => Some(IMG_WOOD_FLOOR),
+        TileType::Door => Some(IMG_DOOR),
+        TileType::Chest => Some(IMG_CHEST),
+        TileType::Workbench => Some(IMG_WORKBENCH),
+        _ => None,
+    }
+}
+
 fn what_to_draw_tile(tile_type: &TileType) -> Option<String> {
     match *tile_type {
         TileType::GrassFloor | TileType::Tree => Some(IMG_GRASS_FLOOR.to_string()),
@@ -655,51 +1199,3 @@ fn what_to_draw_tile(tile_type: &TileType) -> Option<String> {
         _ => None,
     }
 }
-// struct Island {
-//     pub tiles: Vec<Vec<Tile>>,
-//     pub x: f64,
-//     pub y: f64,
-// }
-
-// impl Island {
-//     fn new() -> Self {
-//         Island {
-//             tiles: vec![vec![]],
-//             x: 0.0,
-//             y: 0.0
-//         }
-//     }
-// }
-
-// fn generate_weighted_circle(size: usize) -> Vec<Vec<f64>> {
-//     let mut circle_arr = vec![vec![0.0; size]; size];
-//     let sizef = size as f64;
-//     let middle: f64 = (sizef - 1) / 2.0;
-//     for i in 0..size {
-//         for j in 0..size {
-//             let x: f64 = middle - i as f64;
-//             let x = x * x;
-//             let y: f64 = middle - j as f64;
-//             let y = y * y;
-//             let val = (middle - (x + y).sqrt()) / middle;
-//             let val = val * 1.5;
-//             let val = val.max(0.0);
-//             let val = val.min(1.0);
-//             circle_arr[i][j] = val;
-//         }
-//     }
-//     circle_arr
-// }
-// const ISLAND_MEAN: f64 = 75.0;
-// const ISLAND_STANDERD_DEV: f64 = 10.0;
-// const ISLAND_LOWERBOUND: f64 = 10.0;
-// const ISLAND_UPPERBOUND: f64 = 200.0;
-// fn generate_island_size() -> usize {
-//     let mut normal = distributions::normal::Normal::new(ISLAND_MEAN, ISLAND_STANDERD_DEV);
-//     let mut island_size = normal.sample(&mut thread_rng());
-//     while island_size <= ISLAND_LOWERBOUND && island_size >= ISLAND_UPPERBOUND {
-//         island_size = normal.sample(&mut thread_rng());
-//     }
-//     let island_size = island_size as usize;
-//     island_size
-// }
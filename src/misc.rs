@@ -1,6 +1,8 @@
 //! Homes miscellaneous features for the game.
 
 use piston_window::{ButtonState, Key};
+use std::fmt;
+use std::io;
 
 pub trait Moveable {
     fn handle_input(&mut self, state: &ButtonState, key: &Option<Key>);
@@ -31,4 +33,90 @@ impl Direction {
             Direction::E => "E".to_string(),
         }
     }
+
+    /*
+        Gives a unit vector pointing in the direction, in screen coordinates.
+
+        @return (f64, f64) The horizontal and vertical parts.
+    */
+    pub fn vector(&self) -> (f64, f64) {
+        match *self {
+            Direction::N => (0.0, -1.0),
+            Direction::E => (1.0, 0.0),
+            Direction::S => (0.0, 1.0),
+            Direction::W => (-1.0, 0.0),
+        }
+    }
+}
+
+/*
+    Errors that can occur while reading one of the game's text files. Parse errors
+    name the kind of file and point at the line that went wrong, counting from 1.
+    Missing errors name something the game needs that the file left out.
+*/
+#[derive(Debug)]
+pub enum DataError {
+    Io(io::Error),
+    Parse {
+        kind: &'static str,
+        line: usize,
+        message: String,
+    },
+    Missing {
+        kind: &'static str,
+        id: String,
+    },
+}
+
+impl DataError {
+    /*
+        Builds a parse error.
+
+        @param kind The kind of file being read, e.g. "save".
+        @param line The line that went wrong.
+        @param message What was wrong with it.
+        @return DataError The error.
+    */
+    pub fn parse(kind: &'static str, line: usize, message: &str) -> Self {
+        DataError::Parse {
+            kind,
+            line,
+            message: message.to_string(),
+        }
+    }
+
+    /*
+        Builds a parse error that also points at a column, counting from 1.
+
+        @param kind The kind of file being read, e.g. "blueprint".
+        @param line The line that went wrong.
+        @param column Where on the line it went wrong.
+        @param message What was wrong with it.
+        @return DataError The error.
+    */
+    pub fn parse_at(kind: &'static str, line: usize, column: usize, message: &str) -> Self {
+        DataError::parse(kind, line, &format!("column {}: {}", column, message))
+    }
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DataError::Io(ref e) => write!(f, "could not read file: {}", e),
+            DataError::Parse {
+                kind,
+                line,
+                ref message,
+            } => write!(f, "bad {} on line {}: {}", kind, line, message),
+            DataError::Missing { kind, ref id } => {
+                write!(f, "{} '{}' is needed but not defined", kind, id)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for DataError {
+    fn from(e: io::Error) -> Self {
+        DataError::Io(e)
+    }
 }
@@ -0,0 +1,342 @@
+//! Grid pathfinding over the world map and the ship's deck, using A*.
+//!
+//! The ground and the deck are separate layers, joined wherever the ship has a portal:
+//! a portal tile on the deck links to the ground tile directly beneath it. Diagonal steps
+//! follow the same rule as walking - a creature is a whole tile wide, so it may only cut
+//! a corner when both tiles beside the diagonal are passable.
+//!
+//! Searches can be spread over several ticks, expanding only so many nodes at a time.
+
+use constants::IMAGE_SIZE_SCALED;
+use map::{tile_coord, Map};
+use ship::Ship;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use tile::TileType;
+
+const STRAIGHT_COST: u32 = 10;
+const DIAGONAL_COST: u32 = 14;
+const PORTAL_COST: u32 = 10;
+pub const MAX_SEARCH_NODES: usize = 4096; // A search gives up after expanding this many nodes.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Layer {
+    Ground,
+    Deck,
+}
+
+/**
+    A tile on one of the layers. Ground nodes use map tile coordinates; deck nodes
+    use the ship's grid, so deck routes stay valid while the ship moves.
+
+    @field layer Which layer the tile is on.
+    @field x The tile's column.
+    @field y The tile's row.
+*/
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Node {
+    pub layer: Layer,
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Node {
+    /*
+        Node constructor.
+
+        @param layer Which layer the tile is on.
+        @param x The tile's column.
+        @param y The tile's row.
+        @return Node Returns itself.
+    */
+    pub fn new(layer: Layer, x: i32, y: i32) -> Self {
+        Node { layer, x, y }
+    }
+}
+
+/**
+    The walkable world, as seen by the pathfinder.
+
+    @field map The world map.
+    @field ship The ship, whose deck is its own layer.
+*/
+pub struct NavGrid<'a> {
+    map: &'a Map,
+    ship: &'a Ship,
+}
+
+impl<'a> NavGrid<'a> {
+    /*
+        NavGrid constructor.
+
+        @param map The world map.
+        @param ship The ship.
+        @return NavGrid Returns itself.
+    */
+    pub fn new(map: &'a Map, ship: &'a Ship) -> Self {
+        NavGrid { map, ship }
+    }
+
+    /*
+        Whether a node can be stood on. Unloaded ground is treated as impassable.
+
+        @param node The node to check.
+        @return bool Whether the node is passable.
+    */
+    pub fn passable(&self, node: Node) -> bool {
+        let tile = match node.layer {
+            Layer::Ground => self.map.tile(node.x, node.y),
+            Layer::Deck => self.ship.tile_at(node.x, node.y),
+        };
+        tile.is_some_and(|tile| tile.passable)
+    }
+
+    /*
+        Finds the node containing a world position, on the given layer.
+
+        @param layer The layer to look on.
+        @param x The x position in pixels.
+        @param y The y position in pixels.
+        @return Node The node.
+    */
+    pub fn node_at(&self, layer: Layer, x: f64, y: f64) -> Node {
+        match layer {
+            Layer::Ground => Node::new(layer, tile_coord(x), tile_coord(y)),
+            Layer::Deck => {
+                Node::new(layer, tile_coord(x - self.ship.x), tile_coord(y - self.ship.y))
+            }
+        }
+    }
+
+    /*
+        Finds the world position of a node's top left corner.
+
+        @param node The node.
+        @return (f64, f64) The position in pixels.
+    */
+    pub fn position(&self, node: Node) -> (f64, f64) {
+        let (x, y) = (node.x as f64 * IMAGE_SIZE_SCALED, node.y as f64 * IMAGE_SIZE_SCALED);
+        match node.layer {
+            Layer::Ground => (x, y),
+            Layer::Deck => (self.ship.x + x, self.ship.y + y),
+        }
+    }
+
+    /*
+        Finds where a portal leads, if the node is a portal on the deck or the
+        ground beneath one.
+
+        @param node The node.
+        @return Option<Node> The node on the other layer.
+    */
+    pub fn portal_link(&self, node: Node) -> Option<Node> {
+        let centre = IMAGE_SIZE_SCALED / 2.0;
+        let link = match node.layer {
+            Layer::Deck => {
+                let tile = self.ship.tile_at(node.x, node.y)?;
+                if !tile.passable || tile.tile_type != TileType::Portal {
+                    return None;
+                }
+                let (x, y) = self.position(node);
+                self.node_at(Layer::Ground, x + centre, y + centre)
+            }
+            Layer::Ground => {
+                let (x, y) = self.position(node);
+                let above = self.node_at(Layer::Deck, x + centre, y + centre);
+                let tile = self.ship.tile_at(above.x, above.y)?;
+                if tile.tile_type != TileType::Portal {
+                    return None;
+                }
+                above
+            }
+        };
+        if self.passable(link) {
+            Some(link)
+        } else {
+            None
+        }
+    }
+
+    /*
+        Lists the nodes reachable in one step, with the cost of each step.
+    */
+    fn neighbours(&self, node: Node) -> Vec<(Node, u32)> {
+        let mut neighbours = vec![];
+        let step = |dx: i32, dy: i32| Node::new(node.layer, node.x + dx, node.y + dy);
+        for &(dx, dy) in [(0, -1), (1, 0), (0, 1), (-1, 0)].iter() {
+            if self.passable(step(dx, dy)) {
+                neighbours.push((step(dx, dy), STRAIGHT_COST));
+            }
+        }
+        // No cutting corners: both tiles beside the diagonal must be clear.
+        for &(dx, dy) in [(1, -1), (1, 1), (-1, 1), (-1, -1)].iter() {
+            if self.passable(step(dx, dy)) && self.passable(step(dx, 0))
+                && self.passable(step(0, dy))
+            {
+                neighbours.push((step(dx, dy), DIAGONAL_COST));
+            }
+        }
+        if let Some(link) = self.portal_link(node) {
+            neighbours.push((link, PORTAL_COST));
+        }
+        neighbours
+    }
+
+    /*
+        Estimates the cost between two nodes, never overestimating. Deck nodes are
+        placed where the ship is, so the estimate holds across layers, less the
+        up to one tile a portal can be out of line with the ground beneath it.
+    */
+    fn heuristic(&self, a: Node, b: Node) -> u32 {
+        let (ax, ay) = self.position(a);
+        let (bx, by) = self.position(b);
+        let dx = (ax - bx).abs() / IMAGE_SIZE_SCALED;
+        let dy = (ay - by).abs() / IMAGE_SIZE_SCALED;
+        let (long, short) = if dx > dy { (dx, dy) } else { (dy, dx) };
+        let estimate = (long - short) * STRAIGHT_COST as f64 + short * DIAGONAL_COST as f64;
+        let estimate = estimate.floor() as u32;
+        if a.layer == b.layer {
+            estimate
+        } else {
+            estimate.saturating_sub(DIAGONAL_COST)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SearchStatus {
+    Searching,
+    Found(Vec<Node>),
+    NoPath,
+}
+
+// An entry in the open set, ordered so the heap pops the lowest estimate first.
+#[derive(PartialEq, Eq)]
+struct Open {
+    estimate: u32,
+    cost: u32,
+    node: Node,
+}
+
+impl Ord for Open {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .cmp(&self.estimate)
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+impl PartialOrd for Open {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/**
+    Implementation of the Search object: an A* search that can be resumed each tick.
+
+    @field goal Where the search is headed.
+    @field status How the search stands.
+    @field open Nodes waiting to be expanded.
+    @field came_from The best known step into each node.
+    @field costs The cheapest known cost to each node.
+    @field expanded How many nodes have been expanded so far.
+*/
+pub struct Search {
+    pub goal: Node,
+    pub status: SearchStatus,
+    open: BinaryHeap<Open>,
+    came_from: HashMap<Node, Node>,
+    costs: HashMap<Node, u32>,
+    expanded: usize,
+}
+
+impl Search {
+    /*
+        Search constructor. Nothing is expanded until the search is stepped.
+
+        @param start Where the route begins.
+        @param goal Where the route ends.
+        @return Search Returns itself.
+    */
+    pub fn new(start: Node, goal: Node) -> Self {
+        let mut open = BinaryHeap::new();
+        open.push(Open {
+            estimate: 0,
+            cost: 0,
+            node: start,
+        });
+        let mut costs = HashMap::new();
+        costs.insert(start, 0);
+        Search {
+            goal,
+            status: SearchStatus::Searching,
+            open,
+            came_from: HashMap::new(),
+            costs,
+            expanded: 0,
+        }
+    }
+
+    /*
+        Continues the search, expanding at most a budget of nodes.
+
+        @param grid The walkable world.
+        @param budget How many nodes may be expanded this time.
+        @return usize How much of the budget was used.
+    */
+    pub fn step(&mut self, grid: &NavGrid, budget: usize) -> usize {
+        let mut used = 0;
+        while self.status == SearchStatus::Searching && used < budget {
+            let current = match self.open.pop() {
+                Some(current) => current,
+                None => {
+                    self.status = SearchStatus::NoPath;
+                    break;
+                }
+            };
+            if current.cost > self.costs[&current.node] {
+                continue; // A cheaper way here was already expanded.
+            }
+            if current.node == self.goal {
+                self.status = SearchStatus::Found(self.route_to(self.goal));
+                break;
+            }
+            used += 1;
+            self.expanded += 1;
+            if self.expanded > MAX_SEARCH_NODES {
+                self.status = SearchStatus::NoPath;
+                break;
+            }
+            for (next, step_cost) in grid.neighbours(current.node) {
+                let cost = current.cost + step_cost;
+                if self.costs.get(&next).is_some_and(|&known| known <= cost) {
+                    continue;
+                }
+                self.costs.insert(next, cost);
+                self.came_from.insert(next, current.node);
+                self.open.push(Open {
+                    estimate: cost + grid.heuristic(next, self.goal),
+                    cost,
+                    node: next,
+                });
+            }
+        }
+        used
+    }
+
+    /*
+        Walks back from a node to the start.
+    */
+    fn route_to(&self, node: Node) -> Vec<Node> {
+        let mut route = vec![node];
+        let mut current = node;
+        while let Some(&previous) = self.came_from.get(&current) {
+            route.push(previous);
+            current = previous;
+        }
+        route.reverse();
+        route
+    }
+}
@@ -0,0 +1,434 @@
+//! Saving and loading of the game state.
+//!
+//! Saves are plain text, one record per line, beginning with a version header.
+//! The map is stored as its seed plus every tile changed since generation,
+//! since everything else can be regenerated from the seed.
+
+use creature::{Creature, CreatureKind, CreatureState, Station};
+use inventory::{Inventory, Stack};
+use item::Item;
+use map::WorldSeed;
+use misc::DataError;
+use ship::Ship;
+use std::collections::HashMap;
+use std::fs;
+use std::str::{FromStr, SplitWhitespace};
+use tile::{Tile, TileType};
+use world::{PlayerLocation, World, CHEST_CAPACITY, CHEST_SLOTS};
+
+pub const SAVE_VERSION: u32 = 1; // Only bumped once a released save format changes.
+pub const SAVE_FILE: &str = "aoe_save.txt";
+pub const MAX_SHIP_SIZE: usize = 256; // Widest or tallest ship a save may hold, in tiles.
+const SAVE_HEADER: &str = "AOE_SAVE";
+const KIND: &str = "save"; // Names saves in errors.
+
+/*
+    Writes the game state to a file.
+
+    @param path Where to write the save.
+    @param world The world to save.
+    @return Result<(), DataError> Whether the save was written.
+*/
+pub fn save_to_file(path: &str, world: &World) -> Result<(), DataError> {
+    fs::write(path, serialize(world))?;
+    Ok(())
+}
+
+/*
+    Reads the game state from a file.
+
+    @param path Where to read the save from.
+    @param items The item prototypes the game runs on.
+    @return Result<World, DataError> The restored world.
+*/
+pub fn load_from_file(path: &str, items: &HashMap<String, Item>) -> Result<World, DataError> {
+    let text = fs::read_to_string(path)?;
+    deserialize(&text, items)
+}
+
+/*
+    Converts a world into save text.
+
+    @param world The world to save.
+    @return String The save text.
+*/
+pub fn serialize(world: &World) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("{} {}\n", SAVE_HEADER, SAVE_VERSION));
+    out.push_str(&format!("seed {}\n", world.map.seed.0));
+    out.push_str(&format!("under_portal {}\n", tile_to_string(&world.map.under_portal)));
+
+    // Sorted so the same world always saves to the same text.
+    let mut edits: Vec<_> = world.map.edits().iter().collect();
+    edits.sort_by_key(|&(&pos, _)| pos);
+    for (&(x, y), tile) in edits {
+        out.push_str(&format!("edit {} {} {}\n", x, y, tile_to_string(tile)));
+    }
+    let mut regrowth: Vec<_> = world.map.regrowth().iter().collect();
+    regrowth.sort_by_key(|&(&pos, _)| pos);
+    for (&(x, y), &(ref tile_type, ticks)) in regrowth {
+        out.push_str(&format!("regrow {} {} {:?} {}\n", x, y, tile_type, ticks));
+    }
+    let mut beneath: Vec<_> = world.map.beneath().iter().collect();
+    beneath.sort_by_key(|&(&pos, _)| pos);
+    for (&(x, y), tile) in beneath {
+        out.push_str(&format!("beneath {} {} {}\n", x, y, tile_to_string(tile)));
+    }
+
+    let ship = &world.ship;
+    out.push_str(&format!(
+        "ship {} {} {} {} {} {}\n",
+        ship.x, ship.y, ship.self_vel_x, ship.self_vel_y, ship.width, ship.height
+    ));
+    out.push_str(&format!(
+        "ship_handling {} {} {}\n",
+        ship.drag, ship.turn_response, ship.heavy_cargo
+    ));
+    for (i, column) in ship.tiles.iter().enumerate() {
+        for (j, tile) in column.iter().enumerate() {
+            out.push_str(&format!("ship_tile {} {} {}\n", i, j, tile_to_string(tile)));
+        }
+    }
+    let mut cargo: Vec<_> = ship.cargo().iter().collect();
+    cargo.sort_by_key(|&(&pos, _)| pos);
+    for (&(i, j), hold) in cargo {
+        out.push_str(&stacks_to_string(&format!("cargo {} {}", i, j), hold));
+    }
+    let mut chests: Vec<_> = world.chests.iter().collect();
+    chests.sort_by_key(|&(&pos, _)| pos);
+    for (&(x, y), chest) in chests {
+        out.push_str(&stacks_to_string(&format!("chest {} {}", x, y), chest));
+    }
+
+    let player = &world.player;
+    out.push_str(&format!(
+        "player {} {} {} {:?} {}\n",
+        player.x,
+        player.y,
+        player.health,
+        player.creature_state,
+        world.player_location == PlayerLocation::OnShip
+    ));
+    out.push_str(&format!("needs {} {}\n", player.food, player.stamina));
+    out.push_str(&format!("hotbar {}\n", player.inventory.selected()));
+    out.push_str(&stacks_to_string("inventory", &player.inventory));
+    for item in &world.items_in_game {
+        out.push_str(&format!(
+            "item {} {} {} {}\n",
+            item.id, item.x, item.y, item.on_ship
+        ));
+    }
+    for creature in &world.creatures {
+        let station = match creature.station {
+            Some(station) => format!("{:?}", station),
+            None => "None".to_string(),
+        };
+        out.push_str(&format!(
+            "creature {:?} {} {} {} {} {} {}\n",
+            creature.kind,
+            creature.x,
+            creature.y,
+            creature.health,
+            creature.on_ship,
+            creature.recruited,
+            station
+        ));
+    }
+    out
+}
+
+/*
+    Rebuilds a world from save text.
+
+    @param text The save text.
+    @param items The item prototypes the game runs on, which saved items are rebuilt from.
+    @return Result<World, DataError> The restored world.
+*/
+pub fn deserialize(text: &str, items: &HashMap<String, Item>) -> Result<World, DataError> {
+    let mut lines = text.lines().enumerate();
+
+    // Header must come first.
+    let (_, header) = lines.next().ok_or_else(|| DataError::parse(KIND, 0, "empty save"))?;
+    let mut fields = Fields::new(header, 1);
+    if fields.next_str()? != SAVE_HEADER {
+        return Err(DataError::parse(KIND, 1, "missing save header"));
+    }
+    let version: u32 = fields.next()?;
+    if version != SAVE_VERSION {
+        return Err(DataError::parse(KIND, 1, &format!("unsupported save version {}", version)));
+    }
+
+    // The seed comes first, as everything else is restored on top of a fresh world.
+    let (_, seed_line) = lines.next().ok_or_else(|| DataError::parse(KIND, 1, "missing seed"))?;
+    let mut fields = Fields::new(seed_line, 2);
+    if fields.next_str()? != "seed" {
+        return Err(DataError::parse(KIND, 2, "missing seed"));
+    }
+    let mut world = World::new(WorldSeed(fields.next()?));
+    world.item_prototypes = items.clone();
+    world.items_in_game.clear();
+    world.player.inventory.clear();
+    let mut has_ship = false;
+
+    for (index, line) in lines {
+        let line_number = index + 1;
+        let mut fields = Fields::new(line, line_number);
+        let record = match fields.iter.next() {
+            Some(record) => record,
+            None => continue, // Blank line.
+        };
+        match record {
+            "under_portal" => world.map.under_portal = fields.next_tile()?,
+            "edit" => {
+                let x = fields.next()?;
+                let y = fields.next()?;
+                world.map.set_tile(x, y, fields.next_tile()?);
+            }
+            "regrow" => {
+                let x = fields.next()?;
+                let y = fields.next()?;
+                let name = fields.next_str()?;
+                let tile_type = TileType::from_name(name).ok_or_else(|| {
+                    DataError::parse(KIND, line_number, &format!("unknown tile type {}", name))
+                })?;
+                world.map.set_regrowth(x, y, tile_type, fields.next()?);
+            }
+            "beneath" => {
+                let x = fields.next()?;
+                let y = fields.next()?;
+                world.map.set_beneath(x, y, fields.next_tile()?);
+            }
+            "ship" => {
+                let mut s = Ship::new(vec![vec![0]]);
+                s.x = fields.next()?;
+                s.y = fields.next()?;
+                s.self_vel_x = fields.next()?;
+                s.self_vel_y = fields.next()?;
+                let width: usize = fields.next()?;
+                let height: usize = fields.next()?;
+                if width < 1 || height < 1 || width > MAX_SHIP_SIZE || height > MAX_SHIP_SIZE {
+                    return Err(DataError::parse(KIND, line_number, "bad ship size"));
+                }
+                s.width = width as f64;
+                s.height = height as f64;
+                s.tiles = vec![vec![Tile::new(TileType::Air); height]; width];
+                world.ship = s;
+                has_ship = true;
+            }
+            "ship_handling" => {
+                if !has_ship {
+                    return Err(DataError::parse(KIND, line_number, "ship handling before ship"));
+                }
+                world.ship.drag = fields.next()?;
+                world.ship.turn_response = fields.next()?;
+                world.ship.heavy_cargo = fields.next()?;
+            }
+            "ship_tile" => {
+                if !has_ship {
+                    return Err(DataError::parse(KIND, line_number, "ship tile before ship"));
+                }
+                let s = &mut world.ship;
+                let i: usize = fields.next()?;
+                let j: usize = fields.next()?;
+                if i >= s.tiles.len() || j >= s.tiles[i].len() {
+                    return Err(DataError::parse(KIND, line_number, "ship tile out of bounds"));
+                }
+                s.tiles[i][j] = fields.next_tile()?;
+            }
+            "cargo" => {
+                let i = fields.next()?;
+                let j = fields.next()?;
+                let hold = world.ship.hold_mut(i, j).ok_or_else(|| {
+                    DataError::parse(KIND, line_number, "cargo outside a cargo hold")
+                })?;
+                next_stack(&mut fields, items, hold)?;
+            }
+            "chest" => {
+                let x = fields.next()?;
+                let y = fields.next()?;
+                let chest = world
+                    .chests
+                    .entry((x, y))
+                    .or_insert_with(|| Inventory::new(CHEST_SLOTS, CHEST_CAPACITY));
+                next_stack(&mut fields, items, chest)?;
+            }
+            "player" => {
+                let player = &mut world.player;
+                player.x = fields.next()?;
+                player.y = fields.next()?;
+                player.health = fields.next()?;
+                player.creature_state = match fields.next_str()? {
+                    "Normal" => CreatureState::Normal,
+                    "ControllingShip" => CreatureState::ControllingShip,
+                    other => {
+                        let message = format!("unknown state {}", other);
+                        return Err(DataError::parse(KIND, line_number, &message));
+                    }
+                };
+                world.player_location = if fields.next()? {
+                    PlayerLocation::OnShip
+                } else {
+                    PlayerLocation::InWorld
+                };
+            }
+            "needs" => {
+                world.player.food = fields.next()?;
+                world.player.stamina = fields.next()?;
+            }
+            "hotbar" => {
+                let index: usize = fields.next()?;
+                if index >= world.player.inventory.size() {
+                    return Err(DataError::parse(KIND, line_number, "hotbar slot out of bounds"));
+                }
+                world.player.inventory.select(index);
+            }
+            "inventory" => {
+                next_stack(&mut fields, items, &mut world.player.inventory)?;
+            }
+            "item" => {
+                let id = fields.next_str()?;
+                let x = fields.next()?;
+                let y = fields.next()?;
+                let mut item = clone_item(items, id, x, y, line_number)?;
+                item.on_ship = fields.next()?;
+                world.items_in_game.push(item);
+            }
+            "creature" => {
+                let name = fields.next_str()?;
+                let kind = CreatureKind::from_name(name).ok_or_else(|| {
+                    DataError::parse(KIND, line_number, &format!("unknown creature {}", name))
+                })?;
+                let mut creature = Creature::with_kind(kind);
+                creature.x = fields.next()?;
+                creature.y = fields.next()?;
+                creature.health = fields.next()?;
+                creature.on_ship = fields.next()?;
+                creature.recruited = fields.next()?;
+                creature.station = match fields.next_str()? {
+                    "None" => None,
+                    name => Some(Station::from_name(name).ok_or_else(|| {
+                        DataError::parse(KIND, line_number, &format!("unknown station {}", name))
+                    })?),
+                };
+                world.creatures.push(creature);
+            }
+            other => {
+                let message = format!("unknown record {}", other);
+                return Err(DataError::parse(KIND, line_number, &message));
+            }
+        }
+    }
+
+    if !has_ship {
+        return Err(DataError::parse(KIND, 0, "missing ship"));
+    }
+    world.ship.recompute_stats();
+    let (x, y) = (world.player.x, world.player.y);
+    world.map.load_around(x, y);
+
+    Ok(world)
+}
+
+/*
+    Formats every stack in an inventory as a record each: the prefix, then the slot,
+    item id and count.
+*/
+fn stacks_to_string(prefix: &str, inventory: &Inventory) -> String {
+    let mut out = String::new();
+    for index in 0..inventory.size() {
+        if let Some(stack) = inventory.slot(index) {
+            out.push_str(&format!("{} {} {} {}\n", prefix, index, stack.item.id, stack.count));
+        }
+    }
+    out
+}
+
+/*
+    Reads a slot, item id and count from a record, and puts the stack into an inventory.
+*/
+fn next_stack(
+    fields: &mut Fields,
+    items: &HashMap<String, Item>,
+    inventory: &mut Inventory,
+) -> Result<(), DataError> {
+    let line = fields.line;
+    let index: usize = fields.next()?;
+    let id = fields.next_str()?;
+    let item = clone_item(items, id, 0.0, 0.0, line)?;
+    let count = fields.next()?;
+    if index >= inventory.size() {
+        return Err(DataError::parse(KIND, line, "inventory slot out of bounds"));
+    }
+    if count == 0 || count > item.max_stack() {
+        return Err(DataError::parse(KIND, line, "bad stack size"));
+    }
+    inventory.set_slot(index, Some(Stack { item, count }));
+    Ok(())
+}
+
+/*
+    Writes a tile as its type, texture flag and health.
+*/
+fn tile_to_string(tile: &Tile) -> String {
+    format!("{:?} {} {}", tile.tile_type, tile.texture, tile.health)
+}
+
+/*
+    Rebuilds a saved item from its prototype.
+*/
+fn clone_item(
+    prototypes: &HashMap<String, Item>,
+    id: &str,
+    x: f64,
+    y: f64,
+    line: usize,
+) -> Result<Item, DataError> {
+    prototypes
+        .get(id)
+        .map(|prototype| prototype.generate_clone(x, y))
+        .ok_or_else(|| DataError::parse(KIND, line, &format!("unknown item {}", id)))
+}
+
+/*
+    Walks the whitespace separated fields of one save line.
+
+    @field iter The remaining fields.
+    @field line The line number, for errors.
+*/
+struct Fields<'a> {
+    iter: SplitWhitespace<'a>,
+    line: usize,
+}
+
+impl<'a> Fields<'a> {
+    fn new(text: &'a str, line: usize) -> Self {
+        Fields {
+            iter: text.split_whitespace(),
+            line,
+        }
+    }
+
+    fn next_str(&mut self) -> Result<&'a str, DataError> {
+        let line = self.line;
+        self.iter
+            .next()
+            .ok_or_else(|| DataError::parse(KIND, line, "missing field"))
+    }
+
+    fn next<T: FromStr>(&mut self) -> Result<T, DataError> {
+        let field = self.next_str()?;
+        field
+            .parse()
+            .map_err(|_| DataError::parse(KIND, self.line, &format!("bad value {}", field)))
+    }
+
+    fn next_tile(&mut self) -> Result<Tile, DataError> {
+        let name = self.next_str()?;
+        let tile_type = TileType::from_name(name)
+            .ok_or_else(|| DataError::parse(KIND, self.line, &format!("unknown tile {}", name)))?;
+        let mut tile = Tile::new(tile_type);
+        tile.texture = self.next()?;
+        tile.health = self.next()?;
+        Ok(tile)
+    }
+}
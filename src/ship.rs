@@ -1,10 +1,31 @@
 //! The Ship object manages the tile vector, position, movement, drawing, and generation of the player's ship.
 
 use tile::*;
+use inventory::Inventory;
 use misc::*;
 use piston_window::*;
 use constants::*;
 use std::collections::HashMap;
+use std::mem;
+
+const DRAG: f64 = 0.04; // Fraction of velocity lost per tick on an axis with no thrust.
+const SPEED_FACTOR: f64 = 12.0; // Top speed of a ship whose thrust matches its weight.
+const MAX_SPEED_CAP: f64 = 12.0;
+const TURN_RESPONSE: f64 = 2.0; // Thrust multiplier when pushing against current velocity.
+const STOP_SPEED: f64 = 0.05; // Below this the ship is considered stopped.
+const HARD_IMPACT_SPEED: f64 = 3.0; // Collisions faster than this damage the hull.
+const HOLD_SLOTS: usize = 16;
+const HOLD_CAPACITY: f64 = 200.0;
+const CARGO_WEIGHT_SCALE: f64 = 0.05; // Ship weight added per unit of cargo weight.
+
+// Crack segments drawn over damaged tiles, as fractions of a tile. More appear as
+// the tile's health drops.
+const CRACKS: [[f64; 4]; 4] = [
+    [0.15, 0.2, 0.5, 0.45],
+    [0.5, 0.45, 0.85, 0.3],
+    [0.5, 0.45, 0.4, 0.85],
+    [0.2, 0.7, 0.4, 0.85],
+];
 
 /**
     Implementation of the Ship object.
@@ -15,9 +36,16 @@ use std::collections::HashMap;
     @field self_vel_x Ship's horizontal velocity.
     @field self_vel_y Ship's vertical velocity.
     @field directions A vector of the ship's direction.
-    @field speed Ship's maximum speed when moving.
+    @field acceleration Velocity gained per tick while a direction is held (derived from tiles).
+    @field drag Fraction of velocity lost per tick on an axis with no thrust.
+    @field max_speed Ship's maximum speed when moving (derived from tiles).
+    @field turn_response Thrust multiplier when pushing against the current velocity.
     @field width Ship's thiccness.
     @field height Ship's height.
+    @field weight Total weight of the ship's tiles, and its cargo if heavy.
+    @field lift Total weight the ship's tiles can hold up.
+    @field cargo What each cargo hold stores, keyed by the hold's grid position.
+    @field heavy_cargo Whether cargo weighs the ship down.
 */
 pub struct Ship {
     pub tiles: Vec<Vec<Tile>>,
@@ -26,9 +54,16 @@ pub struct Ship {
     pub self_vel_x: f64,
     pub self_vel_y: f64,
     directions: Vec<Direction>,
-    speed: f64,
+    pub acceleration: f64,
+    pub drag: f64,
+    pub max_speed: f64,
+    pub turn_response: f64,
     pub width: f64,
     pub height: f64,
+    pub weight: f64,
+    pub lift: f64,
+    cargo: HashMap<(usize, usize), Inventory>,
+    pub heavy_cargo: bool,
 }
 
 impl Ship {
@@ -60,17 +95,67 @@ impl Ship {
             }
         }
 
-        Ship {
-            tiles: temp_tiles,
+        Ship::from_tiles(temp_tiles)
+    }
+
+    /*
+        Builds a ship from a ready-made tile grid, indexed by column then row.
+
+        @param tiles The ship's tiles.
+        @return Ship Returns itself.
+    */
+    pub fn from_tiles(tiles: Vec<Vec<Tile>>) -> Self {
+        let w = tiles.len();
+        let h = tiles[0].len();
+        let mut ship = Ship {
+            tiles,
             x: 0.0,
             y: 0.0,
             self_vel_x: 0.0,
             self_vel_y: 0.0,
             directions: vec![],
-            speed: 6.0,
+            acceleration: 0.0,
+            drag: DRAG,
+            max_speed: 0.0,
+            turn_response: TURN_RESPONSE,
             width: w as f64,
             height: h as f64,
-        }
+            weight: 0.0,
+            lift: 0.0,
+            cargo: HashMap::new(),
+            heavy_cargo: true,
+        };
+        ship.recompute_stats();
+        ship
+    }
+
+    /*
+        Recomputes the ship's performance from its tiles. Thrust against weight sets
+        acceleration and top speed; a ship heavier than its lift can carry is
+        sluggish in proportion. Must be called whenever the tiles or cargo change.
+    */
+    pub fn recompute_stats(&mut self) {
+        let tiles = self.tiles.iter().flat_map(|column| column.iter());
+        let (weight, thrust, lift) = tiles.fold((0.0, 0.0, 0.0), |(w, t, l), tile| {
+            (
+                w + tile.tile_type.weight(),
+                t + tile.tile_type.thrust(),
+                l + tile.tile_type.lift(),
+            )
+        });
+        let weight = if self.heavy_cargo {
+            let cargo: f64 = self.cargo.values().map(|hold| hold.weight()).sum();
+            weight + cargo * CARGO_WEIGHT_SCALE
+        } else {
+            weight
+        };
+        let power = if weight > 0.0 { thrust / weight } else { 0.0 };
+        let lift_factor = if weight > lift { lift / weight } else { 1.0 };
+
+        self.weight = weight;
+        self.lift = lift;
+        self.acceleration = power * lift_factor;
+        self.max_speed = (SPEED_FACTOR * power.sqrt() * lift_factor).min(MAX_SPEED_CAP);
     }
 
     /*
@@ -86,9 +171,294 @@ impl Ship {
         self.y + self.self_vel_y
     }
 
+    /*
+        Brings the ship to an immediate stop, e.g. when anchoring at a portal.
+    */
+    pub fn stop(&mut self) {
+        self.self_vel_x = 0.0;
+        self.self_vel_y = 0.0;
+    }
+
+    /*
+        Handles the ship striking terrain at some speed along one axis.
+        Hard impacts damage the tiles that hit; gentle ones just stop the ship.
+
+        @param speed The speed at which the ship hit.
+        @param hits Indices of the ship tiles that struck terrain.
+        @return i32 The damage dealt to each tile.
+    */
+    pub fn impact(&mut self, speed: f64, hits: &[(usize, usize)]) -> i32 {
+        let speed = speed.abs();
+        if speed <= HARD_IMPACT_SPEED {
+            return 0;
+        }
+        let damage = speed.round() as i32;
+        for &(i, j) in hits {
+            self.damage_tile(i, j, damage);
+        }
+        damage
+    }
+
+    /*
+        Damages a single ship tile. Tiles with no health left are destroyed,
+        leaving Air behind.
+
+        @param i The tile's column.
+        @param j The tile's row.
+        @param damage The amount of health to remove.
+    */
+    pub fn damage_tile(&mut self, i: usize, j: usize, damage: i32) {
+        let tile = &mut self.tiles[i][j];
+        if tile.tile_type == TileType::Air {
+            return;
+        }
+        tile.health -= damage;
+        if tile.health <= 0 {
+            // A destroyed hold loses its cargo overboard.
+            *tile = Tile::new(TileType::Air);
+            self.cargo.remove(&(i, j));
+            self.recompute_stats();
+        }
+    }
+
+    /*
+        Restores a ship tile to full health. Destroyed tiles cannot be repaired.
+
+        @param i The tile's column.
+        @param j The tile's row.
+    */
+    pub fn repair_tile(&mut self, i: usize, j: usize) {
+        let tile = &mut self.tiles[i][j];
+        tile.health = tile.tile_type.max_health();
+    }
+
+    /*
+        Checks whether the ship still has a tile of the given type.
+
+        @param tile_type The type to look for.
+        @return bool Whether any tile matches.
+    */
+    pub fn has_tile(&self, tile_type: TileType) -> bool {
+        self.tiles
+            .iter()
+            .any(|column| column.iter().any(|tile| tile.tile_type == tile_type))
+    }
+
+    /*
+        Looks into a cargo hold.
+
+        @param i The hold's column.
+        @param j The hold's row.
+        @return Option<&Inventory> What the hold stores, if it has been opened.
+    */
+    pub fn hold(&self, i: usize, j: usize) -> Option<&Inventory> {
+        self.cargo.get(&(i, j))
+    }
+
+    /*
+        Opens a cargo hold to store or take things, setting it up the first time.
+
+        @param i The hold's column.
+        @param j The hold's row.
+        @return Option<&mut Inventory> What the hold stores, if the tile is a hold.
+    */
+    pub fn hold_mut(&mut self, i: usize, j: usize) -> Option<&mut Inventory> {
+        let is_hold = self.tiles
+            .get(i)
+            .and_then(|column| column.get(j))
+            .is_some_and(|tile| tile.tile_type == TileType::CargoHold);
+        if !is_hold {
+            return None;
+        }
+        Some(
+            self.cargo
+                .entry((i, j))
+                .or_insert_with(|| Inventory::new(HOLD_SLOTS, HOLD_CAPACITY)),
+        )
+    }
+
+    /*
+        Retrieves every cargo hold that has been opened.
+
+        @return &HashMap<(usize, usize), Inventory> The holds' contents keyed by grid position.
+    */
+    pub fn cargo(&self) -> &HashMap<(usize, usize), Inventory> {
+        &self.cargo
+    }
+
+    /*
+        Gets the tile at a grid position, which may lie outside the grid.
+
+        @param i The tile's column.
+        @param j The tile's row.
+        @return Option<&Tile> The tile, if within the grid.
+    */
+    pub fn tile_at(&self, i: i32, j: i32) -> Option<&Tile> {
+        if i < 0 || j < 0 {
+            return None;
+        }
+        self.tiles
+            .get(i as usize)
+            .and_then(|column| column.get(j as usize))
+    }
+
+    /*
+        Whether a deck tile can be built at a grid position: the cell must be
+        empty and touch an existing part of the ship.
+
+        @param i The tile's column, possibly outside the grid.
+        @param j The tile's row, possibly outside the grid.
+        @return bool Whether a tile can be placed.
+    */
+    pub fn can_place(&self, i: i32, j: i32) -> bool {
+        let is_solid = |i, j| {
+            self.tile_at(i, j)
+                .is_some_and(|tile| tile.tile_type != TileType::Air)
+        };
+        !is_solid(i, j)
+            && (is_solid(i - 1, j)
+                || is_solid(i + 1, j)
+                || is_solid(i, j - 1)
+                || is_solid(i, j + 1))
+    }
+
+    /*
+        Whether the tile at a grid position can be torn up. The wheel and portal
+        are fixtures, and holds must be emptied first; everything else can be removed.
+
+        @param i The tile's column.
+        @param j The tile's row.
+        @return bool Whether the tile can be removed.
+    */
+    pub fn can_remove(&self, i: i32, j: i32) -> bool {
+        let emptied = i < 0 || j < 0
+            || self.hold(i as usize, j as usize).is_none_or(|hold| hold.is_empty());
+        emptied && self.tile_at(i, j).is_some_and(|tile| {
+            !matches!(
+                tile.tile_type,
+                TileType::Air | TileType::Wheel | TileType::Portal
+            )
+        })
+    }
+
+    /*
+        Changes the tile at a grid position. The grid grows to fit positions
+        outside it and shrinks away empty edges afterwards, moving the ship's
+        origin so that existing tiles stay where they are in the world.
+
+        @param i The tile's column, possibly outside the grid.
+        @param j The tile's row, possibly outside the grid.
+        @param tile The new tile.
+        @return (i32, i32) How many columns and rows were added to the left and top
+            (negative if removed), for converting old grid positions to new ones.
+    */
+    pub fn change_tile(&mut self, i: i32, j: i32, tile: Tile) -> (i32, i32) {
+        let air = Tile::new(TileType::Air);
+
+        // Grow to fit.
+        let grow_left = (-i).max(0);
+        let grow_top = (-j).max(0);
+        let grow_right = (i + 1 - self.width as i32).max(0);
+        let grow_bottom = (j + 1 - self.height as i32).max(0);
+        let height = (self.height as i32 + grow_top + grow_bottom) as usize;
+        for column in &mut self.tiles {
+            for _ in 0..grow_top {
+                column.insert(0, air.clone());
+            }
+            column.resize(height, air.clone());
+        }
+        for _ in 0..grow_left {
+            self.tiles.insert(0, vec![air.clone(); height]);
+        }
+        for _ in 0..grow_right {
+            self.tiles.push(vec![air.clone(); height]);
+        }
+        self.tiles[(i + grow_left) as usize][(j + grow_top) as usize] = tile;
+
+        // Shrink empty edges, always keeping at least one cell.
+        let is_empty = |tiles: &[Tile]| tiles.iter().all(|t| t.tile_type == TileType::Air);
+        let mut shrink_left = 0;
+        while self.tiles.len() > 1 && is_empty(&self.tiles[0]) {
+            self.tiles.remove(0);
+            shrink_left += 1;
+        }
+        while self.tiles.len() > 1 && is_empty(&self.tiles[self.tiles.len() - 1]) {
+            self.tiles.pop();
+        }
+        let row_is_empty = |tiles: &Vec<Vec<Tile>>, j: usize| {
+            tiles.iter().all(|c| c[j].tile_type == TileType::Air)
+        };
+        let mut shrink_top = 0;
+        while self.tiles[0].len() > 1 && row_is_empty(&self.tiles, 0) {
+            for column in &mut self.tiles {
+                column.remove(0);
+            }
+            shrink_top += 1;
+        }
+        while self.tiles[0].len() > 1 && row_is_empty(&self.tiles, self.tiles[0].len() - 1) {
+            for column in &mut self.tiles {
+                column.pop();
+            }
+        }
+
+        // Keep dimensions and world position in sync with the grid.
+        let shift_x = grow_left - shrink_left;
+        let shift_y = grow_top - shrink_top;
+        self.x -= shift_x as f64 * IMAGE_SIZE_SCALED;
+        self.y -= shift_y as f64 * IMAGE_SIZE_SCALED;
+        self.width = self.tiles.len() as f64;
+        self.height = self.tiles[0].len() as f64;
+
+        // Cargo moves along with its hold, and goes with it if the hold is replaced.
+        let cargo = mem::take(&mut self.cargo);
+        for ((i, j), hold) in cargo {
+            let (i, j) = (i as i32 + shift_x, j as i32 + shift_y);
+            if self.tile_at(i, j).is_some_and(|tile| tile.tile_type == TileType::CargoHold) {
+                self.cargo.insert((i as usize, j as usize), hold);
+            }
+        }
+        self.recompute_stats();
+        (shift_x, shift_y)
+    }
+
+    /*
+        Sums the health of every tile on the ship.
+
+        @return (i32, i32) The current and maximum hull health.
+    */
+    pub fn hull(&self) -> (i32, i32) {
+        self.tiles
+            .iter()
+            .flat_map(|column| column.iter())
+            .fold((0, 0), |(health, max), tile| {
+                (health + tile.health, max + tile.tile_type.max_health())
+            })
+    }
+
+    /*
+        Computes the next velocity along a single axis.
+
+        @param velocity Current velocity along the axis.
+        @param thrust -1, 0 or 1 depending on the held direction.
+        @return f64 The new velocity.
+    */
+    fn axis_velocity(&self, velocity: f64, thrust: f64) -> f64 {
+        if thrust == 0.0 {
+            let velocity = velocity * (1.0 - self.drag);
+            if velocity.abs() < STOP_SPEED {
+                0.0
+            } else {
+                velocity
+            }
+        } else if velocity * thrust < 0.0 {
+            velocity + thrust * self.acceleration * self.turn_response
+        } else {
+            velocity + thrust * self.acceleration
+        }
+    }
+
     /*
         Resets the ship's direction.
-        Currently unused.
     */
     pub fn reset_dir(&mut self) {
         self.directions = vec![];
@@ -186,8 +556,68 @@ impl Ship {
                             graphics,
                         );
                     }
+                    TileType::Sail
+                    | TileType::Propeller
+                    | TileType::Balloon
+                    | TileType::CargoHold
+                    | TileType::Workbench
+                    | TileType::RepairPost => {
+                        let transform = context
+                            .transform
+                            .trans(
+                                self.x + i as f64 * IMAGE_SIZE_SCALED,
+                                self.y + j as f64 * IMAGE_SIZE_SCALED,
+                            )
+                            .trans(trans_x, trans_y)
+                            .scale(IMAGE_SCALE, IMAGE_SCALE);
+                        let overlay = match self.tiles[i][j].tile_type {
+                            TileType::Sail => IMG_SAIL,
+                            TileType::Propeller => IMG_PROPELLER,
+                            TileType::Balloon => IMG_BALLOON,
+                            TileType::Workbench => IMG_WORKBENCH,
+                            TileType::RepairPost => IMG_REPAIR_POST,
+                            _ => IMG_CARGO_HOLD,
+                        };
+                        for img in &[IMG_WOOD_FLOOR, overlay] {
+                            image(
+                                textures
+                                    .get(*img)
+                                    .unwrap_or_else(|| panic!("Not found: {:?}", img)),
+                                transform,
+                                graphics,
+                            );
+                        }
+                    }
                     _ => {}
                 }
+
+                let tile = &self.tiles[i][j];
+                if tile.is_damaged() {
+                    let transform = context
+                        .transform
+                        .trans(
+                            self.x + i as f64 * IMAGE_SIZE_SCALED,
+                            self.y + j as f64 * IMAGE_SIZE_SCALED,
+                        )
+                        .trans(trans_x, trans_y);
+                    let max_health = tile.tile_type.max_health() as f64;
+                    let damage = 1.0 - tile.health as f64 / max_health;
+                    let count = (damage * CRACKS.len() as f64).ceil() as usize;
+                    for crack in CRACKS.iter().take(count) {
+                        line(
+                            [0.1, 0.05, 0.0, 0.9],
+                            1.0,
+                            [
+                                crack[0] * IMAGE_SIZE_SCALED,
+                                crack[1] * IMAGE_SIZE_SCALED,
+                                crack[2] * IMAGE_SIZE_SCALED,
+                                crack[3] * IMAGE_SIZE_SCALED,
+                            ],
+                            transform,
+                            graphics,
+                        );
+                    }
+                }
             }
         }
     }
@@ -264,44 +694,32 @@ impl Moveable for Ship {
     }
 
     /*
-        Updates ship velocity based on moving direction.
+        Updates ship velocity based on held directions. Called once per tick.
+        Held directions accelerate the ship, faster when reversing; axes without
+        thrust decay by drag until the ship comes to a stop.
     */
     fn update_self_velocity(&mut self) {
-        let mut dx = 0.0;
-        let mut dy = 0.0;
+        let mut thrust_x = 0.0;
+        let mut thrust_y = 0.0;
 
         // Control which direction ship accelerates in.
         for dir in &self.directions {
             match *dir {
-                Direction::N => dy -= self.speed,
-                Direction::S => dy += self.speed,
-                Direction::W => dx -= self.speed,
-                Direction::E => dx += self.speed,
+                Direction::N => thrust_y -= 1.0,
+                Direction::S => thrust_y += 1.0,
+                Direction::W => thrust_x -= 1.0,
+                Direction::E => thrust_x += 1.0,
             }
         }
 
-        self.self_vel_x += dx;
-        self.self_vel_y += dy;
+        self.self_vel_x = self.axis_velocity(self.self_vel_x, thrust_x);
+        self.self_vel_y = self.axis_velocity(self.self_vel_y, thrust_y);
 
-        // Speed throttling.
-        if self.self_vel_x > self.speed {
-            self.self_vel_x = self.speed;
-        }
-        if self.self_vel_x < -self.speed {
-            self.self_vel_x = -self.speed;
-        }
-        if self.self_vel_y > self.speed {
-            self.self_vel_y = self.speed;
-        }
-        if self.self_vel_y < -self.speed {
-            self.self_vel_y = -self.speed;
+        // Speed throttling, keeping diagonal movement no faster than straight.
+        let speed = (self.self_vel_x.powi(2) + self.self_vel_y.powi(2)).sqrt();
+        if speed > self.max_speed {
+            self.self_vel_x *= self.max_speed / speed;
+            self.self_vel_y *= self.max_speed / speed;
         }
     }
 }
-
-// TODO
-// fn change_tile() {}
-
-// pub fn update(&mut self) {
-//     self.update_position();
-// }
@@ -0,0 +1,140 @@
+//! Testing of the creature AI.
+
+#[cfg(test)]
+
+mod tests {
+    use ai::*;
+    use constants::IMAGE_SIZE_SCALED;
+    use creature::*;
+    use misc::*;
+    use rand::{SeedableRng, XorShiftRng};
+
+    fn rng() -> XorShiftRng {
+        XorShiftRng::from_seed([1, 2, 3, 4])
+    }
+
+    fn near_player(x: f64, y: f64) -> Perception {
+        Perception {
+            player: Some((x, y)),
+            food: None,
+            waypoint: None,
+        }
+    }
+
+    #[test]
+    fn wildlife_flees() {
+        let mut rng = rng();
+        let mut deer = Creature::with_kind(CreatureKind::Wildlife);
+        let calm_speed = deer.speed;
+
+        // Far from the player, wildlife just wanders.
+        think(&mut deer, &near_player(20.0 * IMAGE_SIZE_SCALED, 0.0), &mut rng);
+        assert_eq!(deer.brain.state, AiState::Wander);
+
+        // Up close, it runs directly away, faster than it walks.
+        think(&mut deer, &near_player(2.0 * IMAGE_SIZE_SCALED, 0.0), &mut rng);
+        assert_eq!(deer.brain.state, AiState::Flee);
+        assert_eq!(deer.directions, vec![Direction::W]);
+        assert!(deer.self_vel_x < -calm_speed);
+
+        // It keeps running a little past where it got scared.
+        think(&mut deer, &near_player(5.0 * IMAGE_SIZE_SCALED, 0.0), &mut rng);
+        assert_eq!(deer.brain.state, AiState::Flee);
+
+        // Food draws calm wildlife towards it.
+        let perception = Perception {
+            player: None,
+            food: Some((0.0, 3.0 * IMAGE_SIZE_SCALED)),
+            waypoint: None,
+        };
+        think(&mut deer, &perception, &mut rng);
+        assert_eq!(deer.brain.state, AiState::Forage);
+        assert_eq!(deer.directions, vec![Direction::S]);
+    }
+
+    #[test]
+    fn monsters_chase_and_attack() {
+        let mut rng = rng();
+        let mut monster = Creature::with_kind(CreatureKind::Monster);
+
+        let player = (3.0 * IMAGE_SIZE_SCALED, -3.0 * IMAGE_SIZE_SCALED);
+        assert!(!think(&mut monster, &near_player(player.0, player.1), &mut rng));
+        assert_eq!(monster.brain.state, AiState::Chase);
+        assert_eq!(monster.directions, vec![Direction::E, Direction::N]);
+        assert!(monster.self_vel_x > 0.0 && monster.self_vel_y < 0.0);
+
+        // In reach, it stops and strikes, then waits before striking again.
+        let close = near_player(IMAGE_SIZE_SCALED / 2.0, 0.0);
+        assert!(think(&mut monster, &close, &mut rng));
+        assert_eq!(monster.brain.state, AiState::Attack);
+        assert_eq!(monster.self_vel_x, 0.0);
+        let attacks = (0..120).filter(|_| think(&mut monster, &close, &mut rng)).count();
+        assert_eq!(attacks, 2);
+
+        // Without the player around, it goes back to wandering.
+        let alone = Perception {
+            player: None,
+            food: None,
+            waypoint: None,
+        };
+        think(&mut monster, &alone, &mut rng);
+        assert_eq!(monster.brain.state, AiState::Wander);
+    }
+
+    #[test]
+    fn crew_follow_the_captain() {
+        let mut rng = rng();
+        let mut crew = Creature::with_kind(CreatureKind::Crew);
+
+        // Strangers keep to themselves.
+        think(&mut crew, &near_player(-5.0 * IMAGE_SIZE_SCALED, 0.0), &mut rng);
+        assert_eq!(crew.brain.state, AiState::Wander);
+
+        crew.recruited = true;
+        think(&mut crew, &near_player(-5.0 * IMAGE_SIZE_SCALED, 0.0), &mut rng);
+        assert_eq!(crew.brain.state, AiState::Follow);
+        assert_eq!(crew.directions, vec![Direction::W]);
+
+        // Close enough, they wait.
+        think(&mut crew, &near_player(-IMAGE_SIZE_SCALED, 0.0), &mut rng);
+        assert!(crew.directions.is_empty());
+        assert_eq!(crew.self_vel_x, 0.0);
+
+        // With a station, they travel to it and set to work.
+        crew.station = Some(Station::Helm);
+        let to_station = Perception {
+            player: None,
+            food: None,
+            waypoint: Some((0.0, 2.0 * IMAGE_SIZE_SCALED)),
+        };
+        think(&mut crew, &to_station, &mut rng);
+        assert_eq!(crew.brain.state, AiState::Travel);
+        assert_eq!(crew.directions, vec![Direction::S]);
+        think(&mut crew, &near_player(-5.0 * IMAGE_SIZE_SCALED, 0.0), &mut rng);
+        assert_eq!(crew.brain.state, AiState::Work);
+    }
+
+    #[test]
+    fn same_seed_same_decisions() {
+        let run = || {
+            let mut rng = rng();
+            let mut creature = Creature::with_kind(CreatureKind::Wildlife);
+            let alone = Perception {
+                player: None,
+                food: None,
+                waypoint: None,
+            };
+            (0..600)
+                .map(|_| {
+                    think(&mut creature, &alone, &mut rng);
+                    creature.update_position();
+                    (creature.x, creature.y)
+                })
+                .collect::<Vec<_>>()
+        };
+        let path = run();
+        assert_eq!(path, run());
+        // Wandering really goes somewhere.
+        assert!(path.iter().any(|&position| position != (0.0, 0.0)));
+    }
+}
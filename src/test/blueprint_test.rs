@@ -0,0 +1,90 @@
+//! Testing of ship blueprints.
+
+#[cfg(test)]
+
+mod tests {
+    use blueprint::*;
+    use misc::DataError;
+    use ship::*;
+    use tile::*;
+
+    // Reads the column an error message points at.
+    fn column_of(message: &str) -> usize {
+        message["column ".len()..message.find(':').unwrap()].parse().unwrap()
+    }
+
+    #[test]
+    fn default_blueprint_builds() {
+        let blueprint = Blueprint::parse(DEFAULT_BLUEPRINT).expect("default should parse");
+        assert_eq!(blueprint.name, "Default Airship");
+        assert_eq!(blueprint.drag, Some(0.04));
+
+        let ship = blueprint.build();
+        assert_eq!(ship.width, 7.0);
+        assert_eq!(ship.height, 8.0);
+        assert_eq!(ship.max_speed, 6.0);
+        assert_eq!(ship.tiles[3][0].tile_type, TileType::Portal);
+        assert_eq!(ship.tiles[3][2].tile_type, TileType::Wheel);
+        assert_eq!(ship.tiles[0][0].tile_type, TileType::Air);
+    }
+
+    #[test]
+    fn export_round_trips() {
+        let text = "name Skiff\n\
+                    drag 0.5\n\
+                    legend o WoodFloor\n\
+                    legend x Air\n\
+                    legend @ Wheel\n\
+                    layout\n\
+                    xox\n\
+                    o@o\n";
+        let ship = Blueprint::parse(text).unwrap().build();
+        assert_eq!(ship.drag, 0.5);
+        assert_eq!(ship.tiles[1][1].tile_type, TileType::Wheel);
+
+        let exported = export(&ship, "Skiff").unwrap();
+        let reloaded = Blueprint::parse(&exported).unwrap();
+        assert_eq!(reloaded.name, "Skiff");
+        assert_eq!(reloaded.drag, Some(0.5));
+        assert_eq!(
+            reloaded.rows,
+            vec![
+                vec![TileType::Air, TileType::WoodFloor, TileType::Air],
+                vec![TileType::WoodFloor, TileType::Wheel, TileType::WoodFloor],
+            ]
+        );
+        assert_eq!(exported, export(&reloaded.build(), "Skiff").unwrap());
+    }
+
+    #[test]
+    fn errors_point_at_problem() {
+        let error_at = |text: &str| match Blueprint::parse(text) {
+            Err(DataError::Parse { line, message, .. }) => (line, column_of(&message)),
+            _ => panic!("expected parse error for {:?}", text),
+        };
+
+        // Unknown layout character.
+        assert_eq!(error_at("legend . Air\nlayout\n..\n.x\n"), (4, 2));
+        // Ragged rows.
+        assert_eq!(error_at("legend . Air\nlayout\n...\n..\n"), (4, 3));
+        // Unknown tile type, pointing at its name.
+        assert_eq!(error_at("legend . Lava\n"), (1, 10));
+        // Duplicate legend character.
+        assert_eq!(error_at("legend . Air\nlegend . Wheel\n"), (2, 8));
+        // Bad number.
+        assert_eq!(error_at("  drag lots\n"), (1, 8));
+        // Unknown directive.
+        assert_eq!(error_at("\n  colour red\n"), (2, 3));
+        // No layout at all.
+        assert_eq!(error_at("name Empty\n"), (1, 1));
+    }
+    #[test]
+    fn export_refuses_unknown_tiles() {
+        let mut ship = Ship::new(vec![vec![0, 1], vec![1, 0]]);
+        ship.tiles[1][0] = Tile::new(TileType::StoneWall);
+        match export(&ship, "Rock") {
+            Err(DataError::Parse { message, .. }) => assert_eq!(column_of(&message), 2),
+            _ => panic!("expected the stone wall to be refused"),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Testing of crafting recipes.
+
+#[cfg(test)]
+
+mod tests {
+    use crafting::*;
+    use misc::DataError;
+    use item::*;
+    use tile::*;
+
+    #[test]
+    fn default_recipes_parse() {
+        let items = generate_item_prototypes();
+        let book = RecipeBook::parse(DEFAULT_RECIPES, &items).expect("defaults should parse");
+        let planks = book.recipes.iter().find(|r| r.name == "Planks").unwrap();
+        assert_eq!(planks.inputs, vec![("logs".to_string(), 1)]);
+        assert_eq!(planks.outputs, vec![("planks".to_string(), 4)]);
+        assert_eq!(planks.station, Some(TileType::Workbench));
+        let bisket = book.recipes.iter().find(|r| r.name == "Bisket").unwrap();
+        assert_eq!(bisket.station, None);
+    }
+
+    #[test]
+    fn bad_recipes_say_where() {
+        let items = generate_item_prototypes();
+        let line_of = |text: &str| match RecipeBook::parse(text, &items) {
+            Err(DataError::Parse { line, .. }) => line,
+            _ => panic!("expected a parse error in {:?}", text),
+        };
+        assert_eq!(line_of("input logs 1\n"), 1);
+        assert_eq!(line_of("recipe A\ninput logs 1\noutput gold 1\n"), 3);
+        assert_eq!(line_of("recipe A\ninput logs none\noutput planks 1\n"), 2);
+        assert_eq!(line_of("recipe A\nstation Anvil\n"), 2);
+        assert_eq!(line_of("\nrecipe A\ninput logs 1\n"), 2);
+    }
+}
@@ -91,4 +91,105 @@ mod tests {
 
         drop(test_player);
     }
+
+    #[test]
+    fn kinds_differ() {
+        use creature::*;
+        use misc::*;
+
+        let player = Creature::new();
+        assert_eq!(player.kind, CreatureKind::Player);
+
+        let monster = Creature::with_kind(CreatureKind::Monster);
+        assert!(monster.health > player.health);
+        assert!(monster.speed < player.speed);
+
+        // Each kind draws from its own sprite set.
+        assert_eq!(
+            CreatureKind::Crew.sprite(true, Direction::N, 0),
+            "player_moving_N_1"
+        );
+        assert_eq!(CreatureKind::Monster.sprite(false, Direction::E, 2), "monster_3");
+        assert_eq!(CreatureKind::from_name("Wildlife"), Some(CreatureKind::Wildlife));
+        assert_eq!(CreatureKind::from_name("Dragon"), None);
+    }
+
+    #[test]
+    fn combat_timers() {
+        use creature::*;
+        let mut monster = Creature::with_kind(CreatureKind::Monster);
+
+        // A hit grants a grace period before the next can land.
+        assert!(monster.take_damage(1));
+        assert!(!monster.take_damage(1));
+        assert_eq!(monster.health, 3);
+        for _ in 0..45 {
+            monster.update_timers();
+        }
+        assert!(monster.take_damage(1));
+
+        // Swings must cool down between each other.
+        assert!(monster.start_attack());
+        assert!(monster.is_swinging());
+        assert!(!monster.start_attack());
+        for _ in 0..30 {
+            monster.update_timers();
+        }
+        assert!(!monster.is_swinging());
+        assert!(monster.start_attack());
+    }
+
+    #[test]
+    fn needs_drain_and_recover() {
+        use creature::*;
+        use map::WorldSeed;
+        use misc::Direction;
+        use world::World;
+        let mut player = Creature::new();
+
+        // Sprinting spends stamina and food faster than walking.
+        player.directions = vec![Direction::E];
+        player.sprinting = true;
+        for _ in 0..10 {
+            player.update_needs();
+        }
+        assert!(player.stamina < MAX_STAMINA);
+        assert!(player.food < MAX_FOOD);
+        assert!(player.speed > CreatureKind::Player.base_speed());
+
+        // Resting recovers stamina, and effort beyond it is refused.
+        player.directions = vec![];
+        player.update_needs();
+        assert_eq!(player.speed, CreatureKind::Player.base_speed());
+        assert!(!player.exert(MAX_STAMINA + 1.0));
+
+        // Holding sprint, the player runs until spent, then walks at base speed while
+        // a quarter of their stamina comes back, rather than lurching along.
+        player.directions = vec![Direction::E];
+        player.stamina = MAX_STAMINA;
+        for _ in 0..200 {
+            player.update_needs();
+        }
+        assert_eq!(player.stamina, 0.0);
+        for _ in 0..100 {
+            player.update_needs();
+            assert_eq!(player.speed, CreatureKind::Player.base_speed());
+        }
+        player.update_needs();
+        assert!(player.speed > CreatureKind::Player.base_speed());
+
+        // Eating restores food, and an empty belly costs health over time.
+        player.food = 0.0;
+        let bisket = World::new(WorldSeed(1)).item_prototypes["bisket"].generate_clone(0.0, 0.0);
+        player.inventory.clear();
+        player.pickup_item(bisket);
+        player.use_item();
+        assert_eq!(player.food, 35.0);
+        let health = player.health;
+        player.food = 0.0;
+        for _ in 0..300 {
+            player.update_needs();
+        }
+        assert_eq!(player.health, health - 1);
+    }
 }
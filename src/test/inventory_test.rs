@@ -0,0 +1,89 @@
+//! Testing of the Inventory object.
+
+#[cfg(test)]
+
+mod tests {
+    use inventory::*;
+    use item::*;
+
+    #[test]
+    fn items_stack_up_to_their_limit() {
+        let prototypes = generate_item_prototypes();
+        let grune = prototypes["grune"].generate_clone(0.0, 0.0);
+        let sword = prototypes["sword"].generate_clone(0.0, 0.0);
+        let mut inventory = Inventory::new(2, 100.0);
+
+        // Like resources share a slot until it is full.
+        for _ in 0..20 {
+            assert!(inventory.add(grune.clone()));
+        }
+        assert_eq!(inventory.slot(0).unwrap().count, 20);
+        assert!(inventory.add(grune.clone()));
+        assert_eq!(inventory.slot(1).unwrap().count, 1);
+        assert_eq!(inventory.count("grune"), 21);
+
+        // Swords don't stack, and there are no slots left.
+        assert!(!inventory.add(sword));
+
+        // Removing takes from every slot, and only if there are enough.
+        assert!(!inventory.remove("grune", 22));
+        assert!(inventory.remove("grune", 20));
+        assert_eq!(inventory.count("grune"), 1);
+        assert!(inventory.slot(0).is_none());
+    }
+
+    #[test]
+    fn weight_counts_against_capacity() {
+        let prototypes = generate_item_prototypes();
+        let logs = prototypes["logs"].generate_clone(0.0, 0.0);
+        let mut inventory = Inventory::new(8, 20.0);
+
+        assert!(inventory.add(logs.clone()));
+        assert!(inventory.add(logs.clone()));
+        assert_eq!(inventory.weight(), 16.0);
+        assert!(!inventory.can_add(&logs));
+        assert!(!inventory.add(logs));
+        assert!(inventory.add(prototypes["bisket"].generate_clone(0.0, 0.0)));
+    }
+
+    #[test]
+    fn selected_slot_is_in_hand() {
+        let prototypes = generate_item_prototypes();
+        let mut inventory = Inventory::new(3, 100.0);
+        inventory.add(prototypes["sword"].generate_clone(0.0, 0.0));
+        inventory.select(2);
+        assert!(inventory.held().is_none());
+
+        // New items go into the hand when it is free.
+        inventory.add(prototypes["bisket"].generate_clone(0.0, 0.0));
+        inventory.add(prototypes["bisket"].generate_clone(0.0, 0.0));
+        assert_eq!(inventory.held().unwrap().id, "bisket");
+        assert_eq!(inventory.take_held().unwrap().id, "bisket");
+        assert_eq!(inventory.slot(2).unwrap().count, 1);
+
+        // Slots past the end can't be selected.
+        inventory.select(7);
+        assert_eq!(inventory.selected(), 2);
+        inventory.select(0);
+        assert_eq!(inventory.held().unwrap().id, "sword");
+    }
+
+    #[test]
+    fn stacks_move_between_inventories() {
+        let prototypes = generate_item_prototypes();
+        let mut carried = Inventory::new(2, 100.0);
+        let mut chest = Inventory::new(2, 20.0);
+        for _ in 0..3 {
+            carried.add(prototypes["logs"].generate_clone(0.0, 0.0));
+        }
+        assert!(chest.is_empty());
+
+        // Only as much as the chest can bear goes in.
+        assert_eq!(carried.move_stack(0, &mut chest), 2);
+        assert_eq!(chest.count("logs"), 2);
+        assert_eq!(carried.count("logs"), 1);
+        assert_eq!(chest.move_stack(0, &mut carried), 2);
+        assert!(chest.is_empty());
+        assert_eq!(chest.move_stack(0, &mut carried), 0);
+    }
+}
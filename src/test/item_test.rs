@@ -0,0 +1,77 @@
+//! Testing of item definitions.
+
+#[cfg(test)]
+
+mod tests {
+    use item::*;
+    use misc::DataError;
+    use std::path::Path;
+    use world::required_items;
+
+    #[test]
+    fn default_items_are_defined() {
+        let items = generate_item_prototypes();
+        let sword = &items["sword"];
+        assert_eq!(sword.item_type, ItemType::Interactable);
+        assert_eq!(sword.image(), "sword_item");
+        assert_eq!(sword.damage(), 10);
+        assert_eq!(sword.weight(), 5.0);
+        assert_eq!(sword.max_stack(), 1);
+        assert_eq!(sword.effect(), UseEffect::Swing);
+        assert_eq!(items["bisket"].effect(), UseEffect::Eat(35.0, 1));
+        assert_eq!(items["grune"].effect(), UseEffect::Eat(8.0, 0));
+        // Grune can be eaten, but isn't food that wildlife forage for.
+        assert_eq!(items["grune"].item_type, ItemType::Resource);
+
+        // Every item has a sprite to draw with.
+        for item in items.values() {
+            let file = format!("assets/images/{}.png", item.image());
+            assert!(Path::new(&file).exists(), "missing {}", file);
+        }
+    }
+
+    #[test]
+    fn items_fill_in_defaults() {
+        let items = parse_items("item pebble\ncategory Resource\nsprite grune_item\n").unwrap();
+        let pebble = &items["pebble"];
+        assert_eq!(pebble.damage(), 0);
+        assert_eq!(pebble.weight(), 1.0);
+        assert!(pebble.is_pickupable());
+        assert_eq!(pebble.max_stack(), 1);
+        assert_eq!(pebble.effect(), UseEffect::None);
+
+        // Clones are placed, but otherwise the same.
+        let clone = pebble.generate_clone(3.0, 4.0);
+        assert_eq!((clone.x, clone.y), (3.0, 4.0));
+        assert_eq!(clone.id, "pebble");
+    }
+
+    #[test]
+    fn bad_items_say_where() {
+        let line_of = |text: &str| match parse_items(text) {
+            Err(DataError::Parse { line, .. }) => line,
+            _ => panic!("expected a parse error in {:?}", text),
+        };
+        assert_eq!(line_of("sprite grune_item\n"), 1);
+        assert_eq!(line_of("item a\ncategory Gem\nsprite grune_item\n"), 2);
+        assert_eq!(line_of("\nitem a\nsprite grune_item\n"), 2);
+        assert_eq!(line_of("item a\ncategory Food\nsprite s\neffect fly\n"), 4);
+        assert_eq!(line_of("item a\ncategory Food\nsprite s\neffect eat lots\n"), 4);
+        assert_eq!(line_of("item a\ncategory Food\nsprite s\neffect heal 1\n"), 4);
+        assert_eq!(line_of("item a\ncategory Food\nsprite s\neffect eat 5 1 2\n"), 4);
+        assert_eq!(line_of("item a\ncategory Food\nsprite s\nweight heavy\n"), 4);
+        assert_eq!(line_of("item a\ncategory Food\nsprite s\nitem a\n"), 4);
+    }
+
+    #[test]
+    fn required_items_are_checked() {
+        // The built-in items cover everything the world asks for by id.
+        assert!(check_required(&generate_item_prototypes(), &required_items()).is_ok());
+
+        let items = parse_items("item sword\ncategory Interactable\nsprite sword_item\n").unwrap();
+        match check_required(&items, &required_items()) {
+            Err(DataError::Missing { id, .. }) => assert_eq!(id, "bisket"),
+            _ => panic!("expected a missing item"),
+        }
+    }
+}
@@ -0,0 +1,200 @@
+//! Testing of the Map object.
+
+#[cfg(test)]
+
+mod tests {
+
+    #[test]
+    fn same_seed_same_map() {
+        use map::*;
+
+        let seed = WorldSeed(42);
+        let mut map_a = Map::with_seed(seed);
+        let mut map_b = Map::with_seed(seed);
+
+        // Seed is kept on the map.
+        assert_eq!(map_a.seed, seed);
+
+        // Chunks generated in a different order should still match exactly.
+        map_a.load_chunk(0, 0);
+        map_a.load_chunk(-1, 0);
+        map_b.load_chunk(-1, 0);
+        map_b.load_chunk(0, 0);
+        for i in -32..32 {
+            for j in 0..32 {
+                let tile_a = map_a.tile(i, j).unwrap();
+                let tile_b = map_b.tile(i, j).unwrap();
+                assert_eq!(tile_a.tile_type, tile_b.tile_type);
+                assert_eq!(tile_a.texture, tile_b.texture);
+            }
+        }
+    }
+
+    #[test]
+    fn different_seed_different_map() {
+        use map::*;
+
+        let mut map_a = Map::with_seed(WorldSeed(1));
+        let mut map_b = Map::with_seed(WorldSeed(2));
+        map_a.load_chunk(0, 0);
+        map_b.load_chunk(0, 0);
+
+        let mut differs = false;
+        for i in 0..32 {
+            for j in 0..32 {
+                let tile_a = map_a.tile(i, j).unwrap();
+                let tile_b = map_b.tile(i, j).unwrap();
+                if tile_a.tile_type != tile_b.tile_type || tile_a.texture != tile_b.texture {
+                    differs = true;
+                }
+            }
+        }
+        assert!(differs);
+    }
+
+    #[test]
+    fn chunks_stream_around_position() {
+        use constants::*;
+        use map::*;
+
+        let mut map = Map::with_seed(WorldSeed(7));
+
+        // Nothing is generated up front.
+        assert!(map.tile(0, 0).is_none());
+
+        map.load_around(0.0, 0.0);
+        assert!(map.tile(0, 0).is_some());
+        assert!(map.tile(-1, -1).is_some());
+
+        // Moving far away evicts the old chunks and loads new ones.
+        let far = (CHUNK_SIZE * 10) as f64 * IMAGE_SIZE_SCALED;
+        map.load_around(far, far);
+        assert!(map.tile(0, 0).is_none());
+        assert!(map.tile(CHUNK_SIZE * 10, CHUNK_SIZE * 10).is_some());
+    }
+
+    #[test]
+    fn edits_survive_eviction() {
+        use constants::*;
+        use map::*;
+        use tile::*;
+
+        let mut map = Map::with_seed(WorldSeed(7));
+        map.load_around(0.0, 0.0);
+        map.set_tile(3, 4, Tile::new(TileType::Portal));
+        assert_eq!(map.tile(3, 4).unwrap().tile_type, TileType::Portal);
+
+        let far = (CHUNK_SIZE * 10) as f64 * IMAGE_SIZE_SCALED;
+        map.load_around(far, far);
+        map.load_around(0.0, 0.0);
+        assert_eq!(map.tile(3, 4).unwrap().tile_type, TileType::Portal);
+    }
+
+    #[test]
+    fn islands_separated_by_sky() {
+        use constants::*;
+        use map::*;
+        use tile::*;
+
+        let mut map = Map::with_seed(WorldSeed(3));
+        map.load_around(0.0, 0.0);
+
+        let span = CHUNK_SIZE * CHUNK_LOAD_RADIUS;
+        let mut land = 0;
+        for i in -span..span {
+            for j in -span..span {
+                let tile = map.tile(i, j).unwrap();
+                match map.island_at(i, j) {
+                    // Land only exists within an island's bounds.
+                    None => assert_eq!(tile.tile_type, TileType::Air),
+                    Some(island) => {
+                        assert!(island.contains(i, j));
+                        if tile.tile_type != TileType::Air {
+                            land += 1;
+                        }
+                    }
+                }
+            }
+        }
+        assert!(land > 0);
+    }
+
+    #[test]
+    fn island_edges_fall_off_to_air() {
+        use constants::*;
+        use map::*;
+        use tile::*;
+
+        let mut map = Map::with_seed(WorldSeed(3));
+        map.load_around(0.0, 0.0);
+
+        let island = (-64..64)
+            .flat_map(|i| (-64..64).map(move |j| (i, j)))
+            .filter_map(|(i, j)| map.island_at(i, j))
+            .next()
+            .expect("no island near origin")
+            .clone();
+
+        // Corners of the bounding square are outside the radial mask.
+        let right = island.x + island.size - 1;
+        let bottom = island.y + island.size - 1;
+        for &(i, j) in [(island.x, island.y), (right, island.y), (island.x, bottom)].iter() {
+            map.load_around(i as f64 * IMAGE_SIZE_SCALED, j as f64 * IMAGE_SIZE_SCALED);
+            assert_eq!(map.tile(i, j).unwrap().tile_type, TileType::Air);
+        }
+    }
+
+    #[test]
+    fn harvested_trees_regrow() {
+        use map::*;
+        use tile::*;
+
+        let mut map = Map::with_seed(WorldSeed(7));
+        map.load_around(0.0, 0.0);
+        map.set_tile(3, 4, Tile::new(TileType::Tree));
+
+        // Wearing a tree down is remembered, and the last hit fells it.
+        assert_eq!(map.hit_tile(3, 4, 4), None);
+        assert_eq!(map.edits()[&(3, 4)].health, 6);
+        assert_eq!(map.hit_tile(3, 4, 6), Some(&["logs", "grune"][..]));
+        assert_eq!(map.tile(3, 4).unwrap().tile_type, TileType::GrassFloor);
+        assert_eq!(map.hit_tile(3, 4, 10), None);
+
+        // It grows back in time, but not from under someone's feet.
+        let ticks = TileType::Tree.regrowth_ticks().unwrap();
+        for _ in 0..ticks + 10 {
+            map.update_regrowth(|x, y| (x, y) == (3, 4));
+        }
+        assert_eq!(map.tile(3, 4).unwrap().tile_type, TileType::GrassFloor);
+        map.update_regrowth(|_, _| false);
+        assert_eq!(map.tile(3, 4).unwrap().tile_type, TileType::Tree);
+        assert!(map.regrowth().is_empty());
+
+        // Stone is mined for good.
+        map.set_tile(5, 5, Tile::new(TileType::StoneWall));
+        assert_eq!(map.hit_tile(5, 5, 10), Some(&["stone"][..]));
+        assert_eq!(map.tile(5, 5).unwrap().tile_type, TileType::DirtFloor);
+        assert!(map.regrowth().is_empty());
+    }
+
+    #[test]
+    fn structures_remember_the_ground() {
+        use map::*;
+        use tile::*;
+
+        let mut map = Map::with_seed(WorldSeed(7));
+        map.load_around(0.0, 0.0);
+        map.set_tile(3, 4, Tile::new(TileType::DirtFloor));
+        map.set_regrowth(3, 4, TileType::Tree, 10);
+
+        // Building over a stump stops the tree growing back.
+        map.place_structure(3, 4, Tile::new(TileType::WoodWall));
+        assert!(map.is_structure(3, 4));
+        assert!(map.regrowth().is_empty());
+        assert!(!map.tile(3, 4).unwrap().passable);
+
+        assert_eq!(map.remove_structure(3, 4), Some(TileType::WoodWall));
+        assert_eq!(map.tile(3, 4).unwrap().tile_type, TileType::DirtFloor);
+        assert_eq!(map.remove_structure(3, 4), None);
+    }
+}
@@ -1,4 +1,14 @@
 //! Test library.
 
+mod ai_test;
+mod blueprint_test;
+mod crafting_test;
 mod creature_test;
+mod inventory_test;
+mod item_test;
+mod map_test;
+mod pathfinding_test;
+mod save_test;
 mod ship_test;
+mod timestep_test;
+mod world_test;
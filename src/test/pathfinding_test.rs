@@ -0,0 +1,131 @@
+//! Testing of the pathfinder.
+
+#[cfg(test)]
+
+mod tests {
+    use constants::IMAGE_SIZE_SCALED;
+    use map::*;
+    use pathfinding::*;
+    use ship::Ship;
+    use tile::*;
+
+    // A 10x10 meadow, with walls at the given tiles.
+    fn meadow(walls: &[(i32, i32)]) -> Map {
+        let mut map = Map::with_seed(WorldSeed(5));
+        map.load_chunk(0, 0);
+        for x in 0..10 {
+            for y in 0..10 {
+                map.set_tile(x, y, Tile::new(TileType::GrassFloor));
+            }
+        }
+        for &(x, y) in walls {
+            map.set_tile(x, y, Tile::new(TileType::StoneWall));
+        }
+        map
+    }
+
+    // A ship well out of the way.
+    fn far_ship() -> Ship {
+        let mut ship = Ship::new(vec![vec![1, 3, 1]]);
+        ship.x = 100.0 * IMAGE_SIZE_SCALED;
+        ship
+    }
+
+    fn search(grid: &NavGrid, start: Node, goal: Node) -> SearchStatus {
+        let mut search = Search::new(start, goal);
+        search.step(grid, MAX_SEARCH_NODES + 1);
+        search.status
+    }
+
+    fn ground(x: i32, y: i32) -> Node {
+        Node::new(Layer::Ground, x, y)
+    }
+
+    #[test]
+    fn routes_around_walls() {
+        // A wall down the middle, with a gap at the bottom.
+        let walls: Vec<_> = (0..8).map(|y| (5, y)).collect();
+        let map = meadow(&walls);
+        let ship = far_ship();
+        let grid = NavGrid::new(&map, &ship);
+
+        let route = match search(&grid, ground(2, 2), ground(8, 2)) {
+            SearchStatus::Found(route) => route,
+            other => panic!("expected a route, got {:?}", other),
+        };
+        assert_eq!(route.first(), Some(&ground(2, 2)));
+        assert_eq!(route.last(), Some(&ground(8, 2)));
+        for pair in route.windows(2) {
+            assert!(grid.passable(pair[1]));
+            assert!((pair[0].x - pair[1].x).abs() <= 1 && (pair[0].y - pair[1].y).abs() <= 1);
+        }
+        assert!(route.iter().any(|node| node.y >= 8));
+
+        // Walled in entirely, there is no way through.
+        let walls: Vec<_> = (0..10).map(|y| (5, y)).collect();
+        let map = meadow(&walls);
+        let grid = NavGrid::new(&map, &ship);
+        assert_eq!(search(&grid, ground(2, 2), ground(8, 2)), SearchStatus::NoPath);
+    }
+
+    #[test]
+    fn no_cutting_corners() {
+        let map = meadow(&[(3, 2), (2, 3)]);
+        let ship = far_ship();
+        let grid = NavGrid::new(&map, &ship);
+
+        // The diagonal squeezes between two walls, so the route must go the long way.
+        match search(&grid, ground(2, 2), ground(3, 3)) {
+            SearchStatus::Found(route) => assert!(route.len() > 2),
+            other => panic!("expected a route, got {:?}", other),
+        }
+
+        // With one side open, the diagonal is still blocked.
+        let map = meadow(&[(3, 2)]);
+        let grid = NavGrid::new(&map, &ship);
+        match search(&grid, ground(2, 2), ground(3, 3)) {
+            SearchStatus::Found(route) => assert_eq!(route.len(), 3),
+            other => panic!("expected a route, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn search_spreads_over_ticks() {
+        let map = meadow(&[]);
+        let ship = far_ship();
+        let grid = NavGrid::new(&map, &ship);
+        let expected = search(&grid, ground(0, 0), ground(9, 9));
+
+        let mut search = Search::new(ground(0, 0), ground(9, 9));
+        let mut ticks = 0;
+        while search.status == SearchStatus::Searching {
+            assert!(search.step(&grid, 2) <= 2);
+            ticks += 1;
+        }
+        assert!(ticks > 1);
+        assert_eq!(search.status, expected);
+    }
+
+    #[test]
+    fn crosses_the_portal() {
+        let map = meadow(&[]);
+        let mut ship = Ship::new(vec![vec![1, 3, 1]]);
+        ship.x = 4.0 * IMAGE_SIZE_SCALED;
+        ship.y = 4.0 * IMAGE_SIZE_SCALED;
+        let grid = NavGrid::new(&map, &ship);
+
+        // The deck's portal sits over ground tile (5, 4).
+        let portal = Node::new(Layer::Deck, 1, 0);
+        assert_eq!(grid.portal_link(portal), Some(ground(5, 4)));
+        assert_eq!(grid.portal_link(ground(5, 4)), Some(portal));
+        assert_eq!(grid.portal_link(Node::new(Layer::Deck, 0, 0)), None);
+
+        let route = match search(&grid, Node::new(Layer::Deck, 0, 0), ground(8, 8)) {
+            SearchStatus::Found(route) => route,
+            other => panic!("expected a route, got {:?}", other),
+        };
+        let crossing = route.iter().position(|node| node.layer == Layer::Ground).unwrap();
+        assert_eq!(route[crossing - 1], portal);
+        assert_eq!(route[crossing], ground(5, 4));
+    }
+}
@@ -0,0 +1,144 @@
+//! Testing of saving and loading.
+
+#[cfg(test)]
+
+mod tests {
+    use creature::*;
+    use inventory::*;
+    use item::*;
+    use map::*;
+    use misc::DataError;
+    use save::*;
+    use ship::*;
+    use tile::*;
+    use world::*;
+
+    #[test]
+    fn round_trip() {
+        let mut world = World::new(WorldSeed(99));
+        world.map.set_tile(-5, 12, Tile::new(TileType::Portal));
+        world.map.under_portal = Tile::new(TileType::GrassFloor);
+        world.map.set_regrowth(2, 3, TileType::Tree, 99);
+        world.map.set_beneath(7, 8, Tile::new(TileType::DirtFloor));
+
+        let mut ship = Ship::new(vec![vec![0, 1, 3], vec![1, 2, 1]]);
+        ship.tiles[0][0] = Tile::new(TileType::CargoHold);
+        ship.heavy_cargo = false;
+        let logs = world.item_prototypes["logs"].generate_clone(0.0, 0.0);
+        ship.hold_mut(0, 0).unwrap().set_slot(3, Some(Stack { item: logs, count: 2 }));
+        ship.x = 64.0;
+        ship.y = -32.5;
+        ship.self_vel_x = 1.25;
+        ship.damage_tile(1, 0, 3);
+        ship.drag = 0.1;
+        world.ship = ship;
+
+        world.player.x = 70.0;
+        world.player.y = -10.0;
+        world.player.health = 2;
+        world.player.food = 40.5;
+        world.player.stamina = 12.25;
+        world.player.change_control_state();
+        let bisket = world.item_prototypes["bisket"].generate_clone(0.0, 0.0);
+        world.player.inventory.clear();
+        world.player.pickup_item(bisket.clone());
+        world.player.pickup_item(bisket);
+        world.player.pickup_item(world.item_prototypes["logs"].generate_clone(0.0, 0.0));
+        world.player.inventory.select(1);
+
+        let mut crew = Creature::with_kind(CreatureKind::Crew);
+        crew.x = 12.0;
+        crew.on_ship = true;
+        crew.recruited = true;
+        crew.station = Some(Station::Repair);
+        world.creatures = vec![crew];
+
+        let mut chest = Inventory::new(CHEST_SLOTS, CHEST_CAPACITY);
+        chest.add(world.item_prototypes["stone"].generate_clone(0.0, 0.0));
+        world.chests.insert((-4, 9), chest);
+
+        world.items_in_game = vec![world.item_prototypes["bisket"].generate_clone(3.0, 4.0)];
+        let text = serialize(&world);
+
+        let loaded = deserialize(&text, &world.item_prototypes).expect("save should load");
+        assert_eq!(loaded.map.seed, WorldSeed(99));
+        assert_eq!(loaded.map.edits()[&(-5, 12)].tile_type, TileType::Portal);
+        assert_eq!(loaded.map.under_portal.tile_type, TileType::GrassFloor);
+        assert_eq!(loaded.map.regrowth()[&(2, 3)], (TileType::Tree, 99));
+        assert_eq!(loaded.map.beneath()[&(7, 8)].tile_type, TileType::DirtFloor);
+
+        assert_eq!(loaded.ship.x, 64.0);
+        assert_eq!(loaded.ship.y, -32.5);
+        assert_eq!(loaded.ship.self_vel_x, 1.25);
+        assert_eq!(loaded.ship.tiles[1][0].health, 7);
+        assert_eq!(loaded.ship.drag, 0.1);
+        assert_eq!(loaded.ship.width, 3.0);
+        assert_eq!(loaded.ship.height, 2.0);
+        assert_eq!(loaded.ship.tiles[2][0].tile_type, TileType::Portal);
+        assert_eq!(loaded.ship.tiles[1][1].tile_type, TileType::Wheel);
+        assert!(!loaded.ship.heavy_cargo);
+        assert_eq!(loaded.ship.hold(0, 0).unwrap().slot(3).unwrap().count, 2);
+        assert_eq!(loaded.chests[&(-4, 9)].count("stone"), 1);
+
+        assert_eq!(loaded.player.x, 70.0);
+        assert_eq!(loaded.player.health, 2);
+        assert_eq!(loaded.player.food, 40.5);
+        assert_eq!(loaded.player.stamina, 12.25);
+        assert_eq!(loaded.player.creature_state, CreatureState::ControllingShip);
+        assert_eq!(loaded.player.inventory.slot(0).unwrap().item.id, "bisket");
+        assert_eq!(loaded.player.inventory.slot(0).unwrap().count, 2);
+        assert_eq!(loaded.player.inventory.held().unwrap().id, "logs");
+        assert_eq!(loaded.player_location, PlayerLocation::OnShip);
+
+        assert_eq!(loaded.items_in_game.len(), 1);
+        assert_eq!(loaded.items_in_game[0].x, 3.0);
+
+        assert_eq!(loaded.creatures.len(), 1);
+        assert_eq!(loaded.creatures[0].kind, CreatureKind::Crew);
+        assert_eq!(loaded.creatures[0].x, 12.0);
+        assert!(loaded.creatures[0].on_ship);
+        assert!(loaded.creatures[0].recruited);
+        assert_eq!(loaded.creatures[0].station, Some(Station::Repair));
+
+        // Saving the loaded game gives identical text.
+        assert_eq!(text, serialize(&loaded));
+    }
+
+    #[test]
+    fn items_come_from_the_active_prototypes() {
+        let data = format!(
+            "{}\nitem pebble\ncategory Resource\nsprite grune_item\nstack 5\n",
+            DEFAULT_ITEMS
+        );
+        let items = parse_items(&data).unwrap();
+        let mut world = World::new(WorldSeed(3));
+        world.item_prototypes = items.clone();
+        world.player.inventory.clear();
+        for _ in 0..3 {
+            world.player.pickup_item(items["pebble"].generate_clone(0.0, 0.0));
+        }
+        let text = serialize(&world);
+
+        // Items only defined in the data file load with their own stack limit.
+        let loaded = deserialize(&text, &items).expect("save should load");
+        assert_eq!(loaded.player.inventory.count("pebble"), 3);
+        assert_eq!(loaded.player.inventory.slot(0).unwrap().item.max_stack(), 5);
+        assert!(deserialize(&text, &generate_item_prototypes()).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_saves() {
+        let items = generate_item_prototypes();
+        let line_of = |text: &str| match deserialize(text, &items) {
+            Err(DataError::Parse { line, .. }) => line,
+            _ => panic!("expected a parse error in {:?}", text),
+        };
+        assert_eq!(line_of("AOE_SAVE 999\n"), 1);
+        assert_eq!(line_of("AOE_SAVE 1\nseed 1\nedit 0 0 Lava false\n"), 3);
+        // Ship sizes must be sane, and its tiles within them.
+        assert_eq!(line_of("AOE_SAVE 1\nseed 1\nship 0 0 0 0 0 3\n"), 3);
+        assert_eq!(line_of("AOE_SAVE 1\nseed 1\nship 0 0 0 0 2 99999999\n"), 3);
+        assert_eq!(line_of("AOE_SAVE 1\nseed 1\nship 0 0 0 0 2.5 3\n"), 3);
+        assert_eq!(line_of("AOE_SAVE 1\nseed 1\nship 0 0 0 0 2 2\nship_tile 2 0 Air false\n"), 4);
+    }
+}
@@ -28,4 +28,214 @@ mod tests {
 
         drop(test_ship);
     }
+
+    #[test]
+    fn ship_accelerates_gradually() {
+        use misc::*;
+        use piston_window::{ButtonState, Key};
+        use ship::*;
+
+        let mut test_ship = Ship::new(vec![vec![1, 2], vec![1, 1]]);
+        test_ship.handle_input(&ButtonState::Press, &Some(Key::D));
+
+        // One tick of thrust gives one step of acceleration, not full speed.
+        test_ship.update_self_velocity();
+        assert_eq!(test_ship.self_vel_x, test_ship.acceleration);
+
+        // Holding the key eventually reaches, but never exceeds, max speed.
+        for _ in 0..1000 {
+            test_ship.update_self_velocity();
+        }
+        assert_eq!(test_ship.self_vel_x, test_ship.max_speed);
+
+        // Diagonals are capped too.
+        test_ship.handle_input(&ButtonState::Press, &Some(Key::S));
+        for _ in 0..1000 {
+            test_ship.update_self_velocity();
+        }
+        let speed = (test_ship.self_vel_x.powi(2) + test_ship.self_vel_y.powi(2)).sqrt();
+        assert!((speed - test_ship.max_speed).abs() < 1e-9);
+    }
+
+    #[test]
+    fn drag_brings_ship_to_stop() {
+        use misc::*;
+        use piston_window::{ButtonState, Key};
+        use ship::*;
+
+        let mut test_ship = Ship::new(vec![vec![1, 2], vec![1, 1]]);
+        test_ship.handle_input(&ButtonState::Press, &Some(Key::W));
+        for _ in 0..100 {
+            test_ship.update_self_velocity();
+        }
+        test_ship.handle_input(&ButtonState::Release, &Some(Key::W));
+
+        // Slows a little each tick rather than stopping dead.
+        let before = test_ship.self_vel_y;
+        test_ship.update_self_velocity();
+        assert!(test_ship.self_vel_y < 0.0 && test_ship.self_vel_y > before);
+
+        for _ in 0..1000 {
+            test_ship.update_self_velocity();
+            test_ship.update_position();
+        }
+        assert_eq!(test_ship.self_vel_y, 0.0);
+
+        // No drag means the ship coasts forever.
+        test_ship.drag = 0.0;
+        test_ship.self_vel_x = 2.0;
+        test_ship.update_self_velocity();
+        assert_eq!(test_ship.self_vel_x, 2.0);
+    }
+
+    #[test]
+    fn turn_response_speeds_reversal() {
+        use misc::*;
+        use piston_window::{ButtonState, Key};
+        use ship::*;
+
+        let mut test_ship = Ship::new(vec![vec![1, 2], vec![1, 1]]);
+        test_ship.self_vel_x = 3.0;
+        test_ship.handle_input(&ButtonState::Press, &Some(Key::A));
+        test_ship.update_self_velocity();
+        let expected = 3.0 - test_ship.acceleration * test_ship.turn_response;
+        assert_eq!(test_ship.self_vel_x, expected);
+    }
+
+    #[test]
+    fn tiles_take_damage() {
+        use ship::*;
+        use tile::*;
+
+        let mut test_ship = Ship::new(vec![vec![1, 2], vec![1, 3]]);
+        let (full, max) = test_ship.hull();
+        assert_eq!(full, max);
+
+        // Gentle impacts do nothing, hard ones damage each tile that hit.
+        assert_eq!(test_ship.impact(1.0, &[(0, 0)]), 0);
+        assert_eq!(test_ship.impact(-4.0, &[(0, 0), (0, 1)]), 4);
+        assert_eq!(test_ship.tiles[0][0].health, 6);
+        assert_eq!(test_ship.tiles[0][1].health, 6);
+        assert_eq!(test_ship.hull().0, full - 8);
+
+        // Destroyed tiles become Air.
+        test_ship.damage_tile(1, 1, 6);
+        assert_eq!(test_ship.tiles[1][1].tile_type, TileType::Air);
+        assert!(!test_ship.has_tile(TileType::Portal));
+        assert!(test_ship.has_tile(TileType::Wheel));
+
+        test_ship.repair_tile(0, 0);
+        assert!(!test_ship.tiles[0][0].is_damaged());
+    }
+
+    #[test]
+    fn grid_grows_and_shrinks() {
+        use constants::IMAGE_SIZE_SCALED;
+        use ship::*;
+        use tile::*;
+
+        let mut test_ship = Ship::new(vec![vec![1, 2], vec![1, 3]]);
+        test_ship.x = 100.0;
+
+        // Only empty cells touching the ship can be built on.
+        assert!(test_ship.can_place(-1, 0));
+        assert!(!test_ship.can_place(-1, -1));
+        assert!(!test_ship.can_place(0, 0));
+        assert!(test_ship.can_remove(0, 0));
+        assert!(!test_ship.can_remove(1, 0));
+
+        // Growing left moves the origin so existing tiles stay put.
+        let shift = test_ship.change_tile(-1, 0, Tile::new(TileType::WoodFloor));
+        assert_eq!(shift, (1, 0));
+        assert_eq!(test_ship.width, 3.0);
+        assert_eq!(test_ship.height, 2.0);
+        assert_eq!(test_ship.x, 100.0 - IMAGE_SIZE_SCALED);
+        assert_eq!(test_ship.tiles[2][0].tile_type, TileType::Wheel);
+
+        // Growing down.
+        test_ship.change_tile(1, 2, Tile::new(TileType::WoodFloor));
+        assert_eq!(test_ship.height, 3.0);
+        assert_eq!(test_ship.tiles.len(), 3);
+        assert!(test_ship.tiles.iter().all(|column| column.len() == 3));
+
+        // Removing edge tiles shrinks the grid back.
+        assert_eq!(test_ship.change_tile(0, 0, Tile::new(TileType::Air)), (-1, 0));
+        test_ship.change_tile(0, 2, Tile::new(TileType::Air));
+        assert_eq!(test_ship.width, 2.0);
+        assert_eq!(test_ship.height, 2.0);
+        assert_eq!(test_ship.x, 100.0);
+        assert_eq!(test_ship.tiles[1][0].tile_type, TileType::Wheel);
+    }
+
+    #[test]
+    fn stats_follow_composition() {
+        use ship::*;
+        use tile::*;
+
+        // Weight 48 against the wheel's thrust of 12.
+        let mut layout = vec![vec![1; 6]; 8];
+        layout[2][3] = 2;
+        let mut test_ship = Ship::new(layout);
+        assert_eq!(test_ship.weight, 48.0);
+        assert_eq!(test_ship.acceleration, 0.25);
+        assert_eq!(test_ship.max_speed, 6.0);
+
+        // More deck is heavier and slower.
+        test_ship.change_tile(6, 0, Tile::new(TileType::WoodFloor));
+        assert_eq!(test_ship.weight, 49.0);
+        assert!(test_ship.max_speed < 6.0);
+
+        // Propellers pay for their weight.
+        test_ship.change_tile(6, 0, Tile::new(TileType::Propeller));
+        assert!(test_ship.acceleration > 0.25);
+        assert!(test_ship.max_speed > 6.0);
+
+        // Cargo outweighs the lift, and only balloons bring the ship back up to speed.
+        for j in 0..8 {
+            test_ship.change_tile(7, j, Tile::new(TileType::CargoHold));
+        }
+        assert!(test_ship.weight > test_ship.lift);
+        let laden = test_ship.max_speed;
+        for j in 0..3 {
+            test_ship.change_tile(8, j, Tile::new(TileType::Balloon));
+        }
+        assert!(test_ship.lift >= test_ship.weight);
+        assert!(test_ship.max_speed > laden);
+
+        // Losing tiles updates the stats too.
+        let weight = test_ship.weight;
+        test_ship.damage_tile(8, 0, 100);
+        assert_eq!(test_ship.weight, weight - 1.0);
+    }
+
+    #[test]
+    fn cargo_moves_with_its_hold() {
+        use item::*;
+        use ship::*;
+        use tile::*;
+
+        let mut test_ship = Ship::new(vec![vec![1, 1, 2]]);
+        test_ship.change_tile(1, 0, Tile::new(TileType::CargoHold));
+        assert!(test_ship.hold_mut(0, 0).is_none());
+        let logs = generate_item_prototypes()["logs"].generate_clone(0.0, 0.0);
+        test_ship.hold_mut(1, 0).unwrap().add(logs);
+
+        // Cargo weighs the ship down, unless that is switched off.
+        let weight = test_ship.weight;
+        test_ship.recompute_stats();
+        assert!(test_ship.weight > weight);
+        test_ship.heavy_cargo = false;
+        test_ship.recompute_stats();
+        assert_eq!(test_ship.weight, weight);
+
+        // Growing the grid leftwards carries the cargo along.
+        test_ship.change_tile(-1, 0, Tile::new(TileType::WoodFloor));
+        assert!(test_ship.hold(1, 0).is_none());
+        assert_eq!(test_ship.hold(2, 0).unwrap().count("logs"), 1);
+
+        // A full hold can't be torn up, but is lost if destroyed.
+        assert!(!test_ship.can_remove(2, 0));
+        test_ship.damage_tile(2, 0, 100);
+        assert!(test_ship.cargo().is_empty());
+    }
 }
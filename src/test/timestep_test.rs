@@ -0,0 +1,40 @@
+//! Testing of the fixed timestep.
+
+#[cfg(test)]
+
+mod tests {
+
+    #[test]
+    fn ticks_accumulate() {
+        use timestep::*;
+        let mut timestep = FixedTimestep::new(10.0, 5);
+
+        // Less than a tick banks time without simulating.
+        assert_eq!(timestep.advance(0.05), 0);
+        assert!((timestep.alpha() - 0.5).abs() < 1e-9);
+
+        // The banked time carries into the next update.
+        assert_eq!(timestep.advance(0.06), 1);
+        assert!((timestep.alpha() - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn catch_up_is_capped() {
+        use timestep::*;
+        let mut timestep = FixedTimestep::new(10.0, 5);
+
+        // A two second stall only runs the maximum number of ticks.
+        assert_eq!(timestep.advance(2.0), 5);
+        assert!(timestep.alpha() < 1.0);
+
+        // And the dropped lag isn't simulated later.
+        assert_eq!(timestep.advance(0.0), 0);
+    }
+
+    #[test]
+    fn lerp_interpolates() {
+        use timestep::*;
+        assert_eq!(lerp(10.0, 20.0, 0.0), 10.0);
+        assert_eq!(lerp(10.0, 20.0, 0.25), 12.5);
+    }
+}
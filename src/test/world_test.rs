@@ -0,0 +1,840 @@
+//! Testing of the World object, driven headlessly.
+
+#[cfg(test)]
+
+mod tests {
+    use creature::*;
+    use map::*;
+    use piston_window::{ButtonState, Key};
+    use world::*;
+
+    #[test]
+    fn player_walks_on_deck() {
+        let mut world = World::new(WorldSeed(5));
+        let start_x = world.player.x;
+
+        world.handle_input(&ButtonState::Press, Key::D);
+        world.update();
+        assert!(world.player.x > start_x);
+
+        // Walking long enough stops at the edge of the deck.
+        for _ in 0..200 {
+            world.update();
+        }
+        let edge = world.ship.x + world.ship.width * ::constants::IMAGE_SIZE_SCALED;
+        assert!(world.player.x + ::constants::IMAGE_SIZE_SCALED <= edge);
+
+        world.handle_input(&ButtonState::Release, Key::D);
+        let stopped_x = world.player.x;
+        world.update();
+        assert_eq!(world.player.x, stopped_x);
+    }
+
+    #[test]
+    fn wheel_toggles_steering() {
+        let mut world = World::new(WorldSeed(5));
+
+        // Stand on the wheel of the default ship.
+        world.player.x = world.ship.x + 3.0 * ::constants::IMAGE_SIZE_SCALED;
+        world.player.y = world.ship.y + 2.0 * ::constants::IMAGE_SIZE_SCALED;
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.player.creature_state, CreatureState::ControllingShip);
+
+        // Movement keys now fly the ship, carrying the player along.
+        let (ship_x, player_x) = (world.ship.x, world.player.x);
+        world.handle_input(&ButtonState::Press, Key::D);
+        world.update();
+        assert!(world.ship.x > ship_x);
+        assert_eq!(world.ship.x - ship_x, world.player.x - player_x);
+    }
+
+    #[test]
+    fn same_seed_same_world() {
+        let mut world_a = World::new(WorldSeed(11));
+        let mut world_b = World::new(WorldSeed(11));
+        for _ in 0..3000 {
+            world_a.update();
+            world_b.update();
+        }
+        assert_eq!(world_a.items_in_game.len(), world_b.items_in_game.len());
+        for (a, b) in world_a.items_in_game.iter().zip(world_b.items_in_game.iter()) {
+            assert_eq!((a.x, a.y), (b.x, b.y));
+        }
+    }
+
+    #[test]
+    fn ship_collides_with_terrain() {
+        use constants::IMAGE_SIZE_SCALED;
+        use tile::*;
+
+        let mut world = World::new(WorldSeed(5));
+        let wall_x = world.ship.width as i32 + 3;
+        for x in -4..wall_x + 4 {
+            for y in -20..30 {
+                let tile_type = if x == wall_x {
+                    TileType::StoneWall
+                } else {
+                    TileType::Air
+                };
+                world.map.set_tile(x, y, Tile::new(tile_type));
+            }
+        }
+
+        // Fly hard into the wall while drifting downwards.
+        world.ship.drag = 0.0;
+        world.ship.self_vel_x = 5.0;
+        world.ship.self_vel_y = 1.0;
+        let (hull, _) = world.ship.hull();
+        let offset = world.player.x - world.ship.x;
+        for _ in 0..25 {
+            world.update();
+        }
+
+        // Stopped flush against the wall, damaged, but still sliding along it.
+        let ship_right = world.ship.x + world.ship.width * IMAGE_SIZE_SCALED;
+        assert!(ship_right <= wall_x as f64 * IMAGE_SIZE_SCALED);
+        assert!(ship_right > (wall_x - 1) as f64 * IMAGE_SIZE_SCALED);
+        assert_eq!(world.ship.self_vel_x, 0.0);
+        // Only the leading column of the hull took the hit.
+        let right = world.ship.width as usize - 1;
+        assert!(world.ship.tiles[right][4].is_damaged());
+        assert!(!world.ship.tiles[right - 1][4].is_damaged());
+        assert_eq!(world.ship.hull().0, hull - 5 * 5);
+        assert_eq!(world.ship.y, 25.0);
+        assert_eq!(world.player.x - world.ship.x, offset);
+
+        // Gentle bumps don't hurt.
+        world.ship.self_vel_x = 1.0;
+        world.update();
+        assert_eq!(world.ship.hull().0, hull - 5 * 5);
+    }
+
+    #[test]
+    fn logs_repair_ship() {
+        let mut world = World::new(WorldSeed(5));
+        let (i, j) = (3, 4);
+        world.player.x = world.ship.x + i as f64 * ::constants::IMAGE_SIZE_SCALED;
+        world.player.y = world.ship.y + j as f64 * ::constants::IMAGE_SIZE_SCALED;
+        world.ship.damage_tile(i, j + 1, 4);
+
+        // Nothing to repair with.
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert!(world.ship.tiles[i][j + 1].is_damaged());
+
+        let logs = world.item_prototypes["logs"].generate_clone(0.0, 0.0);
+        world.player.pickup_item(logs);
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert!(!world.ship.tiles[i][j + 1].is_damaged());
+        assert_eq!(world.player.inventory.count("logs"), 0);
+    }
+
+    #[test]
+    fn losing_wheel_stops_steering() {
+        use tile::*;
+
+        let mut world = World::new(WorldSeed(5));
+        world.player.x = world.ship.x + 3.0 * ::constants::IMAGE_SIZE_SCALED;
+        world.player.y = world.ship.y + 2.0 * ::constants::IMAGE_SIZE_SCALED;
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.player.creature_state, CreatureState::ControllingShip);
+
+        world.ship.damage_tile(3, 2, 100);
+        assert_eq!(world.ship.tiles[3][2].tile_type, TileType::Air);
+        assert!(!world.ship.has_tile(TileType::Wheel));
+        world.update();
+        assert_eq!(world.player.creature_state, CreatureState::Normal);
+    }
+
+    #[test]
+    fn wedged_ship_works_itself_free() {
+        use constants::IMAGE_SIZE_SCALED;
+        use tile::*;
+
+        let mut world = World::new(WorldSeed(5));
+        let wall_x = world.ship.width as i32 + 3;
+        for x in -4..wall_x + 4 {
+            for y in -20..30 {
+                let tile_type = if x == wall_x { TileType::StoneWall } else { TileType::Air };
+                world.map.set_tile(x, y, Tile::new(tile_type));
+            }
+        }
+        // Half a tile into the wall.
+        world.ship.x = (wall_x as f64 + 0.5 - world.ship.width) * IMAGE_SIZE_SCALED;
+        world.ship.drag = 0.0;
+        let start_x = world.ship.x;
+
+        // Driving deeper is refused, but sliding along the wall is not.
+        world.ship.self_vel_x = 2.0;
+        world.ship.self_vel_y = 1.0;
+        world.update();
+        assert_eq!(world.ship.x, start_x);
+        assert_eq!(world.ship.self_vel_x, 0.0);
+        assert_eq!(world.ship.y, 1.0);
+
+        // Backing out works.
+        world.ship.self_vel_x = -2.0;
+        world.update();
+        assert_eq!(world.ship.x, start_x - 2.0);
+    }
+
+    #[test]
+    fn build_mode_edits_deck() {
+        use tile::*;
+
+        let mut world = World::new(WorldSeed(5));
+        // Stand at the top-left corner of the deck.
+        world.player.x = world.ship.x + 2.0 * ::constants::IMAGE_SIZE_SCALED;
+        world.player.y = world.ship.y;
+        let (ship_x, width) = (world.ship.x, world.ship.width);
+
+        world.handle_input(&ButtonState::Press, Key::B);
+        assert!(world.build_cursor.is_some());
+        world.handle_input(&ButtonState::Press, Key::Up);
+        world.handle_input(&ButtonState::Press, Key::Left);
+        assert_eq!(world.build_target(), Some((1, 0)));
+
+        // Building needs logs.
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.ship.tiles[1][0].tile_type, TileType::Air);
+
+        let logs = world.item_prototypes["logs"].generate_clone(0.0, 0.0);
+        world.player.pickup_item(logs);
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.ship.tiles[1][0].tile_type, TileType::WoodFloor);
+        assert_eq!(world.player.inventory.count("logs"), 0);
+
+        // Build past the edge of the grid, which grows to fit.
+        world.handle_input(&ButtonState::Press, Key::Up);
+        let logs = world.item_prototypes["logs"].generate_clone(0.0, 0.0);
+        world.player.pickup_item(logs);
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.ship.height, 9.0);
+        assert_eq!(world.ship.width, width);
+        assert_eq!(world.ship.x, ship_x);
+        assert_eq!(world.ship.y, -::constants::IMAGE_SIZE_SCALED);
+        assert_eq!(world.build_target(), Some((1, 0)));
+        assert_eq!(world.ship.tiles[1][0].tile_type, TileType::WoodFloor);
+
+        // Tearing it up refunds the logs and shrinks the grid again.
+        world.handle_input(&ButtonState::Press, Key::Q);
+        assert_eq!(world.ship.height, 8.0);
+        assert_eq!(world.ship.y, 0.0);
+        assert_eq!(world.player.inventory.count("logs"), 1);
+    }
+
+    #[test]
+    fn growing_the_deck_keeps_routes_on_course() {
+        use pathfinding::*;
+
+        let mut world = World::new(WorldSeed(5));
+        world.creatures.clear();
+        let mut crew = Creature::with_kind(CreatureKind::Crew);
+        crew.on_ship = true;
+        crew.brain.route = vec![Node::new(Layer::Deck, 2, 3), Node::new(Layer::Ground, 5, 5)];
+        world.creatures.push(crew);
+
+        // Build a tile above the top row, which grows the grid upwards.
+        world.player.x = world.ship.x + 2.0 * ::constants::IMAGE_SIZE_SCALED;
+        world.player.y = world.ship.y;
+        for _ in 0..2 {
+            let logs = world.item_prototypes["logs"].generate_clone(0.0, 0.0);
+            world.player.pickup_item(logs);
+        }
+        world.handle_input(&ButtonState::Press, Key::B);
+        world.handle_input(&ButtonState::Press, Key::Up);
+        world.handle_input(&ButtonState::Press, Key::Left);
+        world.handle_input(&ButtonState::Press, Key::E);
+        world.handle_input(&ButtonState::Press, Key::Up);
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.ship.height, 9.0);
+
+        // Deck steps follow the tiles they were on; ground steps stay put.
+        let route = &world.creatures[0].brain.route;
+        assert_eq!(route[0], Node::new(Layer::Deck, 2, 4));
+        assert_eq!(route[1], Node::new(Layer::Ground, 5, 5));
+    }
+
+    #[test]
+    fn ship_parts_cost_their_materials() {
+        use tile::*;
+
+        let mut world = World::new(WorldSeed(5));
+        world.player.x = world.ship.x + 2.0 * ::constants::IMAGE_SIZE_SCALED;
+        world.player.y = world.ship.y;
+        world.handle_input(&ButtonState::Press, Key::B);
+        world.handle_input(&ButtonState::Press, Key::D3);
+        world.handle_input(&ButtonState::Press, Key::Up);
+        world.handle_input(&ButtonState::Press, Key::Left);
+        assert_eq!(world.build_tile, TileType::Propeller);
+
+        // One log is not enough for a propeller.
+        let logs = world.item_prototypes["logs"].generate_clone(0.0, 0.0);
+        world.player.pickup_item(logs);
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.ship.tiles[1][0].tile_type, TileType::Air);
+
+        // Four are.
+        for _ in 0..3 {
+            let logs = world.item_prototypes["logs"].generate_clone(0.0, 0.0);
+            world.player.pickup_item(logs);
+        }
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.ship.tiles[1][0].tile_type, TileType::Propeller);
+        assert_eq!(world.player.inventory.count("logs"), 0);
+
+        // Tearing it up gives all four back.
+        world.handle_input(&ButtonState::Press, Key::Q);
+        assert_eq!(world.ship.tiles[1][0].tile_type, TileType::Air);
+        assert_eq!(world.player.inventory.count("logs"), 4);
+    }
+
+    // Drops the player onto open ground on the island in the first region.
+    fn land_on_island(world: &mut World) {
+        for cx in 0..4 {
+            for cy in 0..4 {
+                world.map.load_chunk(cx, cy);
+            }
+        }
+        let ground = |world: &World, x, y| {
+            world.map.island_at(x, y).is_some()
+                && world.map.tile(x, y).is_some_and(|tile| tile.passable)
+        };
+        for x in 0..128 {
+            for y in 0..128 {
+                if ground(world, x, y) && ground(world, x + 1, y) && ground(world, x, y + 1)
+                    && ground(world, x + 1, y + 1)
+                {
+                    world.player.x = x as f64 * ::constants::IMAGE_SIZE_SCALED;
+                    world.player.y = y as f64 * ::constants::IMAGE_SIZE_SCALED;
+                    world.player_location = PlayerLocation::InWorld;
+                    return;
+                }
+            }
+        }
+        panic!("no island ground found");
+    }
+
+    #[test]
+    fn creatures_roam_islands() {
+        let mut world = World::new(WorldSeed(5));
+        land_on_island(&mut world);
+        for _ in 0..6000 {
+            world.update();
+        }
+        assert!(!world.creatures.is_empty());
+        for creature in &world.creatures {
+            let (x, y) = (tile_coord(creature.x), tile_coord(creature.y));
+            assert!(!creature.on_ship);
+            assert!(world.map.island_at(x, y).is_some());
+            assert!(world.map.tile(x, y).unwrap().passable);
+        }
+    }
+
+    #[test]
+    fn crew_ride_the_ship() {
+        let mut world = World::new(WorldSeed(5));
+        let mut crew = Creature::with_kind(CreatureKind::Crew);
+        crew.on_ship = true;
+        crew.x = world.ship.x + 3.0 * ::constants::IMAGE_SIZE_SCALED;
+        crew.y = world.ship.y + 5.0 * ::constants::IMAGE_SIZE_SCALED;
+        world.creatures.push(crew);
+
+        world.ship.self_vel_x = 4.0;
+        for _ in 0..300 {
+            world.update();
+            // Crew wander about the deck but never off it.
+            let crew = &world.creatures[0];
+            assert!(crew.x >= world.ship.x);
+            let deck_end = world.ship.x + world.ship.width * ::constants::IMAGE_SIZE_SCALED;
+            assert!(crew.x + ::constants::IMAGE_SIZE_SCALED <= deck_end);
+        }
+        assert!(world.ship.x > 0.0);
+    }
+
+    #[test]
+    fn monsters_hurt_the_player() {
+        let mut world = World::new(WorldSeed(5));
+        land_on_island(&mut world);
+        let mut monster = Creature::with_kind(CreatureKind::Monster);
+        monster.x = world.player.x;
+        monster.y = world.player.y;
+        world.creatures.push(monster);
+
+        let health = world.player.health;
+        world.update();
+        assert_eq!(world.player.health, health - 1);
+
+        // Aboard the ship, the player is out of reach of monsters on the island.
+        world.player_location = PlayerLocation::OnShip;
+        for _ in 0..120 {
+            world.update();
+        }
+        assert_eq!(world.player.health, health - 1);
+    }
+
+    #[test]
+    fn monsters_find_a_way_around() {
+        use constants::IMAGE_SIZE_SCALED;
+        use tile::*;
+
+        let mut world = World::new(WorldSeed(5));
+        land_on_island(&mut world);
+        let (px, py) = (tile_coord(world.player.x), tile_coord(world.player.y));
+        // A clearing, with a wall between the player and the monster.
+        for x in px - 5..px + 6 {
+            for y in py - 5..py + 6 {
+                let wall = x == px + 2 && (y - py).abs() <= 3;
+                let tile_type = if wall { TileType::StoneWall } else { TileType::GrassFloor };
+                world.map.set_tile(x, y, Tile::new(tile_type));
+            }
+        }
+        let mut monster = Creature::with_kind(CreatureKind::Monster);
+        monster.x = (px + 4) as f64 * IMAGE_SIZE_SCALED;
+        monster.y = world.player.y;
+        world.creatures.push(monster);
+
+        let health = world.player.health;
+        for _ in 0..600 {
+            world.update();
+        }
+        assert!(world.player.health < health);
+    }
+
+    #[test]
+    fn crew_board_and_go_ashore() {
+        use constants::IMAGE_SIZE_SCALED;
+        use tile::*;
+
+        let mut world = World::new(WorldSeed(5));
+        land_on_island(&mut world);
+        let (px, py) = (tile_coord(world.player.x), tile_coord(world.player.y));
+        // A small clearing ringed by trees.
+        for x in px - 2..px + 3 {
+            for y in py - 2..py + 3 {
+                let ring = (x - px).abs() == 2 || (y - py).abs() == 2;
+                let tile_type = if ring { TileType::Tree } else { TileType::GrassFloor };
+                world.map.set_tile(x, y, Tile::new(tile_type));
+            }
+        }
+        // Anchor the ship with its portal, at (3, 0), over the player.
+        world.ship.x = world.player.x - 3.0 * IMAGE_SIZE_SCALED;
+        world.ship.y = world.player.y;
+
+        let mut crew = Creature::with_kind(CreatureKind::Crew);
+        crew.x = world.player.x + IMAGE_SIZE_SCALED;
+        crew.y = world.player.y;
+        world.creatures = vec![crew];
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert!(world.creatures[0].recruited);
+
+        // Recruits make their way aboard through the portal.
+        for _ in 0..300 {
+            world.update();
+        }
+        assert!(world.creatures[0].on_ship);
+
+        // Gatherers go back ashore and fell trees for logs, leaving them to regrow.
+        world.creatures[0].station = Some(Station::Gather);
+        for _ in 0..1800 {
+            world.update();
+        }
+        assert!(!world.creatures[0].on_ship);
+        assert!(world.items_in_game.iter().any(|item| item.id == "logs"));
+        assert!(!world.map.regrowth().is_empty());
+    }
+
+    #[test]
+    fn helmsman_holds_course() {
+        use constants::IMAGE_SIZE_SCALED;
+
+        let mut world = World::new(WorldSeed(5));
+        let mut crew = Creature::with_kind(CreatureKind::Crew);
+        crew.on_ship = true;
+        crew.recruited = true;
+        crew.station = Some(Station::Helm);
+        crew.x = world.ship.x + 3.0 * IMAGE_SIZE_SCALED;
+        crew.y = world.ship.y + 6.0 * IMAGE_SIZE_SCALED;
+        world.creatures.push(crew);
+        for _ in 0..300 {
+            world.update();
+        }
+        // Standing at the wheel.
+        let crew = &world.creatures[0];
+        assert_eq!(crew.x, world.ship.x + 3.0 * IMAGE_SIZE_SCALED);
+        assert_eq!(crew.y, world.ship.y + 2.0 * IMAGE_SIZE_SCALED);
+
+        // Set a course, then let go of the wheel.
+        world.player.x = world.ship.x + 3.0 * IMAGE_SIZE_SCALED;
+        world.player.y = world.ship.y + 2.0 * IMAGE_SIZE_SCALED;
+        world.handle_input(&ButtonState::Press, Key::E);
+        world.handle_input(&ButtonState::Press, Key::D);
+        world.update();
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.player.creature_state, CreatureState::Normal);
+        let speed = world.ship.self_vel_x;
+        for _ in 0..60 {
+            world.update();
+        }
+        assert!(world.ship.self_vel_x > speed);
+
+        // Relieved of duty, the helmsman lets the ship coast.
+        world.creatures[0].station = None;
+        for _ in 0..60 {
+            world.update();
+        }
+        let speed = world.ship.self_vel_x;
+        world.update();
+        assert!(world.ship.self_vel_x < speed);
+    }
+
+    #[test]
+    fn repair_crew_mend_the_hull() {
+        let mut world = World::new(WorldSeed(5));
+        let mut crew = Creature::with_kind(CreatureKind::Crew);
+        crew.on_ship = true;
+        crew.recruited = true;
+        crew.station = Some(Station::Repair);
+        crew.x = world.ship.x + 3.0 * ::constants::IMAGE_SIZE_SCALED;
+        crew.y = world.ship.y + 5.0 * ::constants::IMAGE_SIZE_SCALED;
+        world.creatures.push(crew);
+        world.ship.damage_tile(2, 6, 3);
+
+        // Repairs take logs, so nothing is mended without any.
+        for _ in 0..400 {
+            world.update();
+        }
+        assert!(world.ship.tiles[2][6].is_damaged());
+
+        // With a log to hand, the crew member at the repair post mends the hull.
+        let logs = world.item_prototypes["logs"].generate_clone(0.0, 0.0);
+        world.player.pickup_item(logs);
+        for _ in 0..400 {
+            world.update();
+        }
+        assert!(!world.ship.tiles[2][6].is_damaged());
+        assert_eq!(world.player.inventory.count("logs"), 0);
+    }
+
+    // Clears the ground around the player, so fights aren't cut short by walls.
+    fn clear_around_player(world: &mut World) {
+        use tile::*;
+        let (px, py) = (tile_coord(world.player.x), tile_coord(world.player.y));
+        for x in px - 4..px + 5 {
+            for y in py - 4..py + 5 {
+                world.map.set_tile(x, y, Tile::new(TileType::GrassFloor));
+            }
+        }
+    }
+
+    #[test]
+    fn sword_swings_in_an_arc() {
+        use constants::IMAGE_SIZE_SCALED;
+
+        let mut world = World::new(WorldSeed(5));
+        land_on_island(&mut world);
+        clear_around_player(&mut world);
+
+        // The player faces south, with a monster ahead and an animal behind.
+        let mut monster = Creature::with_kind(CreatureKind::Monster);
+        monster.x = world.player.x;
+        monster.y = world.player.y + IMAGE_SIZE_SCALED;
+        let mut deer = Creature::with_kind(CreatureKind::Wildlife);
+        deer.x = world.player.x;
+        deer.y = world.player.y - IMAGE_SIZE_SCALED;
+        world.creatures = vec![monster, deer];
+
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.creatures.len(), 1);
+        assert_eq!(world.creatures[0].kind, CreatureKind::Wildlife);
+    }
+
+    #[test]
+    fn hits_knock_back_and_grant_grace() {
+        use constants::IMAGE_SIZE_SCALED;
+
+        let mut world = World::new(WorldSeed(5));
+        land_on_island(&mut world);
+        clear_around_player(&mut world);
+        let mut monster = Creature::with_kind(CreatureKind::Monster);
+        monster.x = world.player.x + IMAGE_SIZE_SCALED * 0.75;
+        monster.y = world.player.y;
+        world.creatures = vec![monster];
+
+        let (health, start_x) = (world.player.health, world.player.x);
+        world.update();
+        assert_eq!(world.player.health, health - 1);
+        assert!(world.player.x < start_x);
+
+        // Nothing hurts the player again straight away.
+        for _ in 0..40 {
+            world.update();
+        }
+        assert_eq!(world.player.health, health - 1);
+    }
+
+    #[test]
+    fn thrown_items_slide_to_a_stop() {
+        use tile::*;
+
+        let mut world = World::new(WorldSeed(5));
+        land_on_island(&mut world);
+        let (px, py) = (tile_coord(world.player.x), tile_coord(world.player.y));
+        for x in px - 8..px + 9 {
+            for y in py - 8..py + 9 {
+                world.map.set_tile(x, y, Tile::new(TileType::GrassFloor));
+            }
+        }
+
+        // Lighter things fly further.
+        let mut distances = vec![];
+        for id in ["logs", "bisket"].iter() {
+            world.items_in_game.clear();
+            world.player.inventory.clear();
+            world.player.pickup_item(world.item_prototypes[*id].generate_clone(0.0, 0.0));
+            world.handle_input(&ButtonState::Press, Key::F);
+            assert!(world.player.inventory.held().is_none());
+            world.update();
+            assert!(world.items_in_game[0].is_moving());
+            for _ in 0..120 {
+                world.update();
+            }
+            let item = &world.items_in_game[0];
+            assert!(!item.is_moving());
+            assert_eq!(item.x, world.player.x);
+            distances.push(item.y - world.player.y);
+        }
+        assert!(distances[0] > 0.0);
+        assert!(distances[1] > distances[0]);
+    }
+
+    #[test]
+    fn thrown_items_hurt_creatures() {
+        use constants::IMAGE_SIZE_SCALED;
+
+        let mut world = World::new(WorldSeed(5));
+        land_on_island(&mut world);
+        clear_around_player(&mut world);
+        let mut monster = Creature::with_kind(CreatureKind::Monster);
+        monster.x = world.player.x;
+        monster.y = world.player.y + 2.0 * IMAGE_SIZE_SCALED;
+        world.creatures = vec![monster];
+
+        // The sword flies south, straight into the monster.
+        world.handle_input(&ButtonState::Press, Key::F);
+        for _ in 0..30 {
+            world.update();
+        }
+        assert!(world.creatures.is_empty());
+        let sword = &world.items_in_game[0];
+        assert!(!sword.is_moving());
+        assert!(sword.y < world.player.y + 2.0 * IMAGE_SIZE_SCALED);
+    }
+
+    #[test]
+    fn items_ride_the_deck_or_fall_overboard() {
+        let mut world = World::new(WorldSeed(5));
+        world.items_in_game.clear();
+
+        // Dropped on deck, the sword sails along with the ship.
+        world.handle_input(&ButtonState::Press, Key::Space);
+        let sword_x = world.items_in_game[0].x;
+        world.ship.self_vel_x = 2.0;
+        world.update();
+        assert!(world.items_in_game[0].x > sword_x);
+        world.ship.stop();
+
+        // Thrown, it goes over the side.
+        world.handle_input(&ButtonState::Press, Key::Space);
+        world.handle_input(&ButtonState::Press, Key::F);
+        for _ in 0..60 {
+            world.update();
+        }
+        assert!(world.items_in_game.is_empty());
+    }
+
+    #[test]
+    fn hotbar_picks_what_to_use_and_drop() {
+        let mut world = World::new(WorldSeed(5));
+        world.items_in_game.clear();
+        for _ in 0..2 {
+            let bisket = world.item_prototypes["bisket"].generate_clone(0.0, 0.0);
+            world.player.pickup_item(bisket);
+        }
+        world.player.health = 1;
+
+        // The sword is in hand, so E swings rather than eats.
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.player.health, 1);
+
+        world.handle_input(&ButtonState::Press, Key::D2);
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.player.health, 2);
+        assert_eq!(world.player.inventory.count("bisket"), 1);
+
+        // Space drops the selected item, and picks it back up into the stack.
+        world.handle_input(&ButtonState::Press, Key::Space);
+        assert_eq!(world.items_in_game[0].id, "bisket");
+        assert!(world.player.inventory.held().is_none());
+        world.handle_input(&ButtonState::Press, Key::Space);
+        assert!(world.items_in_game.is_empty());
+        assert_eq!(world.player.inventory.count("bisket"), 1);
+        assert_eq!(world.player.inventory.count("sword"), 1);
+    }
+
+    #[test]
+    fn crafting_needs_inputs_and_stations() {
+        use tile::*;
+
+        let mut world = World::new(WorldSeed(5));
+        let planks = world.recipes.recipes.iter().position(|r| r.name == "Planks").unwrap();
+        let bisket = world.recipes.recipes.iter().position(|r| r.name == "Bisket").unwrap();
+
+        // Nothing to craft with.
+        assert!(!world.craft(bisket));
+
+        for _ in 0..2 {
+            let grune = world.item_prototypes["grune"].generate_clone(0.0, 0.0);
+            world.player.pickup_item(grune);
+        }
+        assert!(world.craft(bisket));
+        assert_eq!(world.player.inventory.count("grune"), 0);
+        assert_eq!(world.player.inventory.count("bisket"), 1);
+
+        // Planks are only made at a workbench.
+        let logs = world.item_prototypes["logs"].generate_clone(0.0, 0.0);
+        world.player.pickup_item(logs);
+        assert!(!world.craft(planks));
+        let (x, y) = (
+            (world.player.x - world.ship.x) / ::constants::IMAGE_SIZE_SCALED,
+            (world.player.y - world.ship.y) / ::constants::IMAGE_SIZE_SCALED,
+        );
+        world.ship.change_tile(x as i32 + 1, y as i32, Tile::new(TileType::Workbench));
+        assert!(world.craft(planks));
+        assert_eq!(world.player.inventory.count("logs"), 0);
+        assert_eq!(world.player.inventory.count("planks"), 4);
+    }
+
+    #[test]
+    fn holding_e_chops_trees() {
+        use tile::*;
+
+        let mut world = World::new(WorldSeed(5));
+        land_on_island(&mut world);
+        clear_around_player(&mut world);
+        world.items_in_game.clear();
+
+        // The player faces south, at a tree.
+        let (x, y) = (tile_coord(world.player.x), tile_coord(world.player.y) + 1);
+        world.map.set_tile(x, y, Tile::new(TileType::Tree));
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.gathering(), Some((x, y)));
+        for _ in 0..100 {
+            world.update();
+        }
+        assert!(world.map.tile(x, y).unwrap().is_damaged());
+
+        // Letting go stops the chopping.
+        world.handle_input(&ButtonState::Release, Key::E);
+        world.update();
+        assert_eq!(world.gathering(), None);
+
+        world.handle_input(&ButtonState::Press, Key::E);
+        for _ in 0..200 {
+            world.update();
+        }
+        assert_eq!(world.map.tile(x, y).unwrap().tile_type, TileType::GrassFloor);
+        let ids: Vec<_> = world.items_in_game.iter().map(|item| item.id.as_str()).collect();
+        assert_eq!(ids, vec!["logs", "grune"]);
+        assert_eq!(world.gathering(), None);
+    }
+
+    #[test]
+    fn build_mode_places_structures_ashore() {
+        use tile::*;
+
+        let mut world = World::new(WorldSeed(5));
+        land_on_island(&mut world);
+        clear_around_player(&mut world);
+        world.player.inventory.clear();
+        let (x, y) = (tile_coord(world.player.x), tile_coord(world.player.y));
+
+        // The cursor starts just below the player, and walls come first.
+        world.handle_input(&ButtonState::Press, Key::B);
+        assert_eq!(world.build_target(), Some((x, y + 1)));
+        world.handle_input(&ButtonState::Press, Key::D1);
+        assert_eq!(world.build_tile, TileType::WoodWall);
+
+        // Nothing to build with.
+        assert!(!world.can_build());
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.map.tile(x, y + 1).unwrap().tile_type, TileType::GrassFloor);
+
+        for _ in 0..3 {
+            let planks = world.item_prototypes["planks"].generate_clone(0.0, 0.0);
+            world.player.pickup_item(planks);
+        }
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.map.tile(x, y + 1).unwrap().tile_type, TileType::WoodWall);
+        assert_eq!(world.player.inventory.count("planks"), 1);
+
+        // Walls can't go where the player stands, or on top of other structures.
+        world.handle_input(&ButtonState::Press, Key::Up);
+        world.player.pickup_item(world.item_prototypes["planks"].generate_clone(0.0, 0.0));
+        assert!(!world.can_build());
+        world.handle_input(&ButtonState::Press, Key::Down);
+        assert!(!world.can_build());
+        assert!(world.can_unbuild());
+
+        // Tearing down gives the planks back and restores the ground.
+        world.handle_input(&ButtonState::Press, Key::Q);
+        assert_eq!(world.map.tile(x, y + 1).unwrap().tile_type, TileType::GrassFloor);
+        assert!(!world.map.is_structure(x, y + 1));
+        assert_eq!(world.player.inventory.count("planks"), 4);
+
+        // Chests cost more.
+        world.handle_input(&ButtonState::Press, Key::D4);
+        assert_eq!(world.build_tile, TileType::Chest);
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.map.tile(x, y + 1).unwrap().tile_type, TileType::Chest);
+        assert_eq!(world.player.inventory.count("planks"), 0);
+    }
+
+    #[test]
+    fn chests_store_what_the_player_carries() {
+        use tile::*;
+
+        let mut world = World::new(WorldSeed(5));
+        land_on_island(&mut world);
+        clear_around_player(&mut world);
+        world.player.inventory.clear();
+        for _ in 0..4 {
+            world.player.pickup_item(world.item_prototypes["planks"].generate_clone(0.0, 0.0));
+        }
+
+        // The player faces south, where a chest is built.
+        world.handle_input(&ButtonState::Press, Key::B);
+        world.handle_input(&ButtonState::Press, Key::D4);
+        world.handle_input(&ButtonState::Press, Key::E);
+        world.handle_input(&ButtonState::Press, Key::B);
+        let (x, y) = (tile_coord(world.player.x), tile_coord(world.player.y) + 1);
+        assert_eq!(world.map.tile(x, y).unwrap().tile_type, TileType::Chest);
+
+        world.player.pickup_item(world.item_prototypes["logs"].generate_clone(0.0, 0.0));
+        world.handle_input(&ButtonState::Press, Key::E);
+        assert_eq!(world.open_container, Some(Container::Chest(x, y)));
+        assert_eq!(world.store(0), 1);
+        assert_eq!(world.player.inventory.count("logs"), 0);
+        assert_eq!(world.container(Container::Chest(x, y)).unwrap().count("logs"), 1);
+
+        // A chest must be emptied before it can be torn down.
+        world.open_container = None;
+        world.handle_input(&ButtonState::Press, Key::B);
+        assert!(!world.can_unbuild());
+        world.open_container = Some(Container::Chest(x, y));
+        assert_eq!(world.retrieve(0), 1);
+        assert!(world.can_unbuild());
+        world.handle_input(&ButtonState::Press, Key::Q);
+        assert!(world.chests.is_empty());
+        assert_eq!(world.player.inventory.count("planks"), 4);
+    }
+}
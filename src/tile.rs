@@ -13,6 +13,161 @@ pub enum TileType {
     Water,
     Wheel,
     Portal,
+    Sail,
+    Propeller,
+    Balloon,
+    CargoHold,
+    Workbench,
+    RepairPost,
+    WoodWall,
+    Door,
+    Chest,
+}
+
+impl TileType {
+    /*
+        Parses a tile type from its name, as written by Debug.
+
+        @param name The tile type's name.
+        @return Option<TileType> The tile type, if the name is known.
+    */
+    pub fn from_name(name: &str) -> Option<TileType> {
+        match name {
+            "WoodFloor" => Some(TileType::WoodFloor),
+            "StoneWall" => Some(TileType::StoneWall),
+            "GrassFloor" => Some(TileType::GrassFloor),
+            "DirtFloor" => Some(TileType::DirtFloor),
+            "Tree" => Some(TileType::Tree),
+            "Air" => Some(TileType::Air),
+            "Water" => Some(TileType::Water),
+            "Wheel" => Some(TileType::Wheel),
+            "Portal" => Some(TileType::Portal),
+            "Sail" => Some(TileType::Sail),
+            "Propeller" => Some(TileType::Propeller),
+            "Balloon" => Some(TileType::Balloon),
+            "CargoHold" => Some(TileType::CargoHold),
+            "Workbench" => Some(TileType::Workbench),
+            "RepairPost" => Some(TileType::RepairPost),
+            "WoodWall" => Some(TileType::WoodWall),
+            "Door" => Some(TileType::Door),
+            "Chest" => Some(TileType::Chest),
+            _ => None,
+        }
+    }
+
+    /*
+        Whether the airship collides with this tile. Stone walls and trees rise
+        high enough to hit the hull; everything else is flown over.
+
+        @return bool Whether the tile blocks the ship.
+    */
+    pub fn blocks_ship(&self) -> bool {
+        matches!(*self, TileType::StoneWall | TileType::Tree)
+    }
+
+    /*
+        What it takes to build a tile of this type, on deck or ashore. Tearing the
+        tile up gives it all back.
+
+        @return &[(&str, u32)] The ids and counts of the items used up.
+    */
+    pub fn build_cost(&self) -> &'static [(&'static str, u32)] {
+        match *self {
+            TileType::WoodFloor => &[("logs", 1)],
+            TileType::Sail => &[("logs", 2)],
+            TileType::Balloon => &[("logs", 1), ("grune", 3)],
+            TileType::CargoHold => &[("logs", 3)],
+            TileType::Propeller => &[("logs", 4)],
+            TileType::Workbench => &[("logs", 1), ("planks", 2)],
+            TileType::RepairPost => &[("logs", 2)],
+            TileType::WoodWall => &[("planks", 2)],
+            TileType::Door => &[("planks", 3)],
+            TileType::Chest => &[("planks", 4)],
+            _ => &[],
+        }
+    }
+
+    /*
+        What harvesting a tile of this type leaves behind, and the items it drops.
+        Trees are chopped down to grass, and stone mined down to dirt.
+
+        @return Option<(TileType, &[&str])> The tile left and the ids of the items
+        dropped, if the tile can be harvested.
+    */
+    pub fn harvest(&self) -> Option<(TileType, &'static [&'static str])> {
+        match *self {
+            TileType::Tree => Some((TileType::GrassFloor, &["logs", "grune"])),
+            TileType::StoneWall => Some((TileType::DirtFloor, &["stone"])),
+            _ => None,
+        }
+    }
+
+    /*
+        How long a harvested tile of this type takes to grow back.
+
+        @return Option<i32> The ticks until it regrows, if it ever does.
+    */
+    pub fn regrowth_ticks(&self) -> Option<i32> {
+        match *self {
+            TileType::Tree => Some(3600),
+            _ => None,
+        }
+    }
+
+    /*
+        The health a tile of this type starts with.
+
+        @return i32 The tile's maximum health.
+    */
+    pub fn max_health(&self) -> i32 {
+        match *self {
+            TileType::Air => 0,
+            TileType::Wheel | TileType::Portal => 6,
+            TileType::Sail | TileType::Balloon => 4,
+            _ => 10,
+        }
+    }
+
+    /*
+        How heavy a ship tile of this type is.
+
+        @return f64 The tile's weight.
+    */
+    pub fn weight(&self) -> f64 {
+        match *self {
+            TileType::Air => 0.0,
+            TileType::Propeller => 2.0,
+            TileType::CargoHold => 3.0,
+            _ => 1.0,
+        }
+    }
+
+    /*
+        How much a ship tile of this type pushes the ship along.
+
+        @return f64 The tile's thrust.
+    */
+    pub fn thrust(&self) -> f64 {
+        match *self {
+            TileType::Wheel => 12.0, // The helm's own engine.
+            TileType::Sail => 2.0,
+            TileType::Propeller => 5.0,
+            _ => 0.0,
+        }
+    }
+
+    /*
+        How much weight a ship tile of this type holds up.
+
+        @return f64 The tile's lift.
+    */
+    pub fn lift(&self) -> f64 {
+        match *self {
+            TileType::Air => 0.0,
+            TileType::Balloon => 8.0,
+            _ => 1.0,
+        }
+    }
 }
 
 /* 
@@ -21,6 +176,7 @@ pub enum TileType {
     @field tile_type The tile's type.
     @field passable Whether the tile can be walked on.
     @field texture Whether the tile will have extra texture (for graphics).
+    @field health The tile's remaining health.
 */
 
 #[derive(Clone)]
@@ -28,6 +184,7 @@ pub struct Tile {
     pub tile_type: TileType,
     pub passable: bool,
     pub texture: bool,
+    pub health: i32,
 }
 
 impl Tile {
@@ -39,13 +196,28 @@ impl Tile {
     */
     pub fn new(tile_type: TileType) -> Self {
         let can_pass = match tile_type {
-            TileType::Water | TileType::StoneWall | TileType::Air => false,
+            TileType::Water
+            | TileType::StoneWall
+            | TileType::Tree
+            | TileType::Air
+            | TileType::WoodWall
+            | TileType::Chest => false,
             _ => true,
         };
         Tile {
+            health: tile_type.max_health(),
             tile_type: tile_type,
             passable: can_pass,
             texture: false,
         }
     }
+
+    /*
+        Whether the tile has taken damage.
+
+        @return bool Whether health is below the maximum.
+    */
+    pub fn is_damaged(&self) -> bool {
+        self.health < self.tile_type.max_health()
+    }
 }
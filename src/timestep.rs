@@ -0,0 +1,80 @@
+//! Fixed-timestep accumulator separating simulation ticks from rendering.
+//!
+//! Real elapsed time is banked in an accumulator and spent in whole ticks of a fixed
+//! length, so movement speeds are the same however fast the machine renders.
+//! The leftover fraction of a tick is used to interpolate positions when drawing.
+
+pub const TICKS_PER_SECOND: f64 = 60.0;
+pub const MAX_TICKS_PER_UPDATE: u32 = 5;
+
+/*
+    Implementation of the FixedTimestep object.
+
+    @field tick_dt Length of one simulation tick in seconds.
+    @field max_ticks Most ticks run for one update, after which lag is dropped.
+    @field accumulator Elapsed time not yet simulated.
+*/
+pub struct FixedTimestep {
+    pub tick_dt: f64,
+    max_ticks: u32,
+    accumulator: f64,
+}
+
+impl FixedTimestep {
+    /*
+        FixedTimestep constructor.
+
+        @param ticks_per_second Simulation rate.
+        @param max_ticks Most ticks to catch up with after a stall.
+        @return FixedTimestep Returns itself.
+    */
+    pub fn new(ticks_per_second: f64, max_ticks: u32) -> Self {
+        FixedTimestep {
+            tick_dt: 1.0 / ticks_per_second,
+            max_ticks,
+            accumulator: 0.0,
+        }
+    }
+
+    /*
+        Banks elapsed time and determines how many ticks to simulate.
+        Any lag beyond max_ticks is thrown away so a long stall doesn't spiral.
+
+        @param elapsed Seconds since the last call.
+        @return u32 Number of ticks to simulate now.
+    */
+    pub fn advance(&mut self, elapsed: f64) -> u32 {
+        self.accumulator += elapsed.max(0.0);
+        let mut ticks = 0;
+        while self.accumulator >= self.tick_dt && ticks < self.max_ticks {
+            self.accumulator -= self.tick_dt;
+            ticks += 1;
+        }
+        if ticks == self.max_ticks && self.accumulator >= self.tick_dt {
+            // Still behind after catching up as far as allowed.
+            self.accumulator %= self.tick_dt;
+        }
+        ticks
+    }
+
+    /*
+        How far the simulation is between the last tick and the next one.
+
+        @return f64 Interpolation factor in [0, 1).
+    */
+    pub fn alpha(&self) -> f64 {
+        self.accumulator / self.tick_dt
+    }
+}
+
+/*
+    Linearly interpolates between a previous and current value.
+
+    @param previous Value at the previous tick.
+    @param current Value at the current tick.
+    @param alpha Interpolation factor.
+    @return f64 The interpolated value.
+*/
+pub fn lerp(previous: f64, current: f64, alpha: f64) -> f64 {
+    previous + (current - previous) * alpha
+}
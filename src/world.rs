@@ -0,0 +1,1837 @@
+//! The World holds the simulation state of the game - the map, ship, player and items -
+//! and steps it with plain input events. It knows nothing about windows or graphics,
+//! so it can be driven headlessly (e.g. in tests), with Game rendering on top of it.
+
+use ai::{self, AiState, Perception, FORAGE_RADIUS};
+use blueprint::{Blueprint, DEFAULT_BLUEPRINT};
+use constants::*;
+use crafting::{Recipe, RecipeBook, DEFAULT_RECIPES};
+use creature::{Creature, CreatureKind, CreatureState, Station};
+use inventory::Inventory;
+use item::*;
+use map::{tile_coord, Map, WorldSeed};
+use misc::*;
+use pathfinding::{Layer, NavGrid, Node, Search, SearchStatus};
+use piston_window::{ButtonState, Key};
+use rand::distributions::Sample;
+use rand::*;
+use ship::Ship;
+use std::collections::HashMap;
+use std::mem;
+use tile::*;
+
+const CREATURE_SPAWN_INTERVAL: i32 = 300; // Ticks between attempts to spawn a creature.
+const MAX_CREATURES: usize = 12;
+const CREW_WORK_INTERVAL: i32 = 120; // Ticks between each bit of work by stationed crew.
+const GATHER_REACH: i32 = 2; // How many tiles away a gatherer can fell trees.
+const RECRUIT_REACH: f64 = 1.5 * IMAGE_SIZE_SCALED;
+const SWORD_REACH: f64 = 1.5 * IMAGE_SIZE_SCALED;
+const SWORD_ARC: f64 = 0.5; // Cosine of the widest angle off the facing direction a swing reaches.
+const SWING_STAMINA: f64 = 15.0; // Stamina spent on each swing of a weapon.
+const KNOCKBACK: f64 = IMAGE_SIZE_SCALED; // How far a hit sends its target flying.
+const PATH_BUDGET: usize = 256; // Nodes all creatures together may search for routes per tick.
+const BUILD_REACH: i32 = 2; // How many tiles from the player the build cursor can go.
+const STATION_REACH: i32 = 1; // How many tiles away a crafting station can be used from.
+const OVERLAP_TOLERANCE: f64 = 0.001; // Rounding allowed when a wedged ship slides along.
+const GATHER_INTERVAL: i32 = 20; // Ticks between each hit while chopping or mining.
+pub const CHEST_SLOTS: usize = 12;
+pub const CHEST_CAPACITY: f64 = 120.0;
+// Ship parts that can be built, selected with the number keys.
+const BUILDABLE_TILES: [TileType; 7] = [
+    TileType::WoodFloor,
+    TileType::Sail,
+    TileType::Propeller,
+    TileType::Balloon,
+    TileType::CargoHold,
+    TileType::Workbench,
+    TileType::RepairPost,
+];
+// Structures that can be built on islands, selected with the number keys.
+const STRUCTURE_TILES: [TileType; 5] = [
+    TileType::WoodWall,
+    TileType::WoodFloor,
+    TileType::Door,
+    TileType::Chest,
+    TileType::Workbench,
+];
+// Items the world spawns or spends by id, besides those harvested or built with.
+const NAMED_ITEMS: [&str; 3] = ["sword", "bisket", "logs"];
+// Tiles that can be chopped or mined.
+const HARVESTED_TILES: [TileType; 2] = [TileType::Tree, TileType::StoneWall];
+
+/*
+    Lists the ids of every item the world refers to directly, which any set of
+    item definitions it runs on has to include.
+
+    @return Vec<&str> The item ids.
+*/
+pub fn required_items() -> Vec<&'static str> {
+    let mut ids = NAMED_ITEMS.to_vec();
+    for tile in HARVESTED_TILES.iter() {
+        if let Some((_, drops)) = tile.harvest() {
+            ids.extend(drops);
+        }
+    }
+    for tile in BUILDABLE_TILES.iter().chain(STRUCTURE_TILES.iter()) {
+        ids.extend(tile.build_cost().iter().map(|&(id, _)| id));
+    }
+    ids
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlayerLocation {
+    OnShip,
+    InWorld,
+}
+
+/*
+    Somewhere things can be stored: a chest on an island, by tile coordinates,
+    or a cargo hold aboard the ship, by grid position.
+*/
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Container {
+    Chest(i32, i32),
+    Hold(usize, usize),
+}
+
+/**
+    Implementation of the World object.
+
+    @field player The main player.
+    @field ship The player's airship.
+    @field player_location Player's worldly position (see above).
+    @field item_prototypes Prototyping pattern for cloning items.
+    @field recipes Every crafting recipe known.
+    @field items_in_game Set of all items in the game.
+    @field chests What each chest on the islands stores, keyed by tile coordinates.
+    @field open_container The container the player has opened to store or take things.
+    @field creatures Every creature other than the player, on islands or aboard the ship.
+    @field map The world map.
+    @field updates_since_last_gen Used for generating new items.
+    @field updates_since_last_spawn Used for spawning new creatures.
+    @field updates_since_last_work Used for pacing the crew's work at their stations.
+    @field updates_since_last_gather Used for pacing the player's chopping and mining.
+    @field gathering The tile the player is chopping or mining, while E is held.
+    @field view_width Width of the area around the player that items spawn in.
+    @field view_height Height of the area around the player that items spawn in.
+    @field build_cursor In build mode, the targeted cell relative to the player's tile.
+    @field build_tile The ship part or structure build mode places.
+    @field rng Gameplay RNG, seeded from the world seed when the world is generated.
+*/
+pub struct World {
+    pub player: Creature,
+    pub ship: Ship,
+    pub player_location: PlayerLocation,
+    pub item_prototypes: HashMap<String, Item>,
+    pub recipes: RecipeBook,
+    pub items_in_game: Vec<Item>,
+    pub chests: HashMap<(i32, i32), Inventory>,
+    pub open_container: Option<Container>,
+    pub creatures: Vec<Creature>,
+    pub map: Map,
+    updates_since_last_gen: i32,
+    updates_since_last_spawn: i32,
+    updates_since_last_work: i32,
+    updates_since_last_gather: i32,
+    gathering: Option<(i32, i32)>,
+    pub view_width: f64,
+    pub view_height: f64,
+    pub build_cursor: Option<(i32, i32)>,
+    pub build_tile: TileType,
+    rng: XorShiftRng,
+}
+
+impl World {
+    /*
+        World constructor. Places the default ship at the origin with the player aboard.
+
+        @param seed The seed driving map generation and gameplay rolls.
+        @return World Returns itself.
+    */
+    pub fn new(seed: WorldSeed) -> Self {
+        let blueprint =
+            Blueprint::parse(DEFAULT_BLUEPRINT).expect("Error in default ship blueprint");
+        World::with_ship(seed, blueprint.build())
+    }
+
+    /*
+        Constructs a world around a given ship, placed at the origin with the player aboard.
+
+        @param seed The seed driving map generation and gameplay rolls.
+        @param ship The player's ship.
+        @return World Returns itself.
+    */
+    pub fn with_ship(seed: WorldSeed, ship: Ship) -> Self {
+        let item_prototypes = generate_item_prototypes();
+        let recipes =
+            RecipeBook::parse(DEFAULT_RECIPES, &item_prototypes).expect("Error in default recipes");
+        let mut world = World {
+            player: Creature::new(),
+            ship,
+            player_location: PlayerLocation::OnShip,
+            item_prototypes,
+            recipes,
+            items_in_game: vec![],
+            chests: HashMap::new(),
+            open_container: None,
+            creatures: vec![],
+            map: Map::with_seed(seed),
+            updates_since_last_gen: 0,
+            updates_since_last_spawn: 0,
+            updates_since_last_work: 0,
+            updates_since_last_gather: 0,
+            gathering: None,
+            view_width: 800.0,
+            view_height: 640.0,
+            build_cursor: None,
+            build_tile: TileType::WoodFloor,
+            rng: seed.rng(),
+        };
+
+        world.ship.x = 0.0; // Initial ship position.
+        world.ship.y = 0.0;
+        world.player.x = world.ship.x + ((world.ship.width / 2.0) * IMAGE_SIZE_SCALED);
+        world.player.y = world.ship.y + ((world.ship.height / 2.0) * IMAGE_SIZE_SCALED);
+        world.map.load_around(world.player.x, world.player.y);
+
+        // Temporary item generation.
+        let mut bisket = world.item_prototypes
+            .get("bisket")
+            .unwrap()
+            .generate_clone(world.ship.x + 128.0, world.ship.y + 128.0);
+        bisket.on_ship = true;
+        world.items_in_game.push(bisket);
+        let sword = world.item_prototypes["sword"].generate_clone(0.0, 0.0);
+        world.player.pickup_item(sword);
+        world
+    }
+
+    /*
+        Input Handling from user.
+
+        @param state The ButtonState.
+        @param key The key pressed or released.
+    */
+    pub fn handle_input(&mut self, state: &ButtonState, key: Key) {
+        use self::Key::*;
+        match key {
+            // Moving.
+            W | A | S | D => self.execute_move(state, &Some(key)),
+            LShift => self.player.sprinting = *state == ButtonState::Press,
+            E if self.build_cursor.is_some() => self.execute_build(state, true),
+            Q if self.build_cursor.is_some() => self.execute_build(state, false),
+            Up | Down | Left | Right => self.execute_move_cursor(state, key),
+            B => self.execute_toggle_build(state),
+            D1 | D2 | D3 | D4 | D5 | D6 | D7 if self.build_cursor.is_some() => {
+                let index = match key {
+                    D1 => 0,
+                    D2 => 1,
+                    D3 => 2,
+                    D4 => 3,
+                    D5 => 4,
+                    D6 => 5,
+                    _ => 6,
+                };
+                if let Some(tile_type) = self.build_options().get(index) {
+                    self.build_tile = tile_type.clone();
+                }
+            }
+            D1 | D2 | D3 | D4 | D5 | D6 | D7 | D8 => self.execute_select_slot(state, key),
+            E => self.execute_action(state),
+            F => self.execute_throw(state),
+            Space => {
+                self.execute_player_hands(state);
+            }
+            _ => {}
+        }
+    }
+
+    /*
+        Finds the cell targeted by the build cursor: a ship cell on deck, or a map
+        tile ashore.
+
+        @return Option<(i32, i32)> The cell's grid or tile position, if in build mode.
+    */
+    pub fn build_target(&self) -> Option<(i32, i32)> {
+        let (dx, dy) = self.build_cursor?;
+        let mut x = self.player.x + IMAGE_SIZE_SCALED / 2.0;
+        let mut y = self.player.y + IMAGE_SIZE_SCALED / 2.0;
+        if self.player_location == PlayerLocation::OnShip {
+            x -= self.ship.x;
+            y -= self.ship.y;
+        }
+        Some((tile_coord(x) + dx, tile_coord(y) + dy))
+    }
+
+    /*
+        The tiles build mode can place where the player is: ship parts on deck,
+        structures ashore.
+
+        @return &[TileType] The choices, in number key order.
+    */
+    pub fn build_options(&self) -> &'static [TileType] {
+        match self.player_location {
+            PlayerLocation::OnShip => &BUILDABLE_TILES,
+            PlayerLocation::InWorld => &STRUCTURE_TILES,
+        }
+    }
+
+    /*
+        Whether building at the build cursor would work. Everything needs the
+        materials it costs. Ship parts need an empty cell beside the deck, and
+        structures open, unoccupied ground that isn't built on yet.
+
+        @return bool Whether the selected tile can be placed.
+    */
+    pub fn can_build(&self) -> bool {
+        let (x, y) = match self.build_target() {
+            Some(target) => target,
+            None => return false,
+        };
+        if !self.can_afford(self.build_tile.build_cost()) {
+            return false;
+        }
+        if self.player_location == PlayerLocation::OnShip {
+            return self.ship.can_place(x, y);
+        }
+        let open = self.map.tile(x, y).is_some_and(|tile| {
+            tile.passable && tile.tile_type != TileType::Portal && !self.map.is_structure(x, y)
+        });
+        let blocked = !Tile::new(self.build_tile.clone()).passable
+            && self.occupied_tiles().contains(&(x, y));
+        open && !blocked
+    }
+
+    /*
+        Whether tearing down at the build cursor would work: deck other than the tile
+        underfoot, or a structure the player built.
+
+        @return bool Whether the targeted tile can be removed.
+    */
+    pub fn can_unbuild(&self) -> bool {
+        let (x, y) = match self.build_target() {
+            Some(target) => target,
+            None => return false,
+        };
+        match self.player_location {
+            PlayerLocation::OnShip => {
+                self.ship.can_remove(x, y) && self.build_cursor != Some((0, 0))
+            }
+            PlayerLocation::InWorld => {
+                let emptied = self.chests.get(&(x, y)).is_none_or(|chest| chest.is_empty());
+                emptied && self.map.is_structure(x, y)
+            }
+        }
+    }
+
+    /*
+        Looks into a container.
+
+        @param container The container.
+        @return Option<&Inventory> What it stores, if it has been opened before.
+    */
+    pub fn container(&self, container: Container) -> Option<&Inventory> {
+        match container {
+            Container::Chest(x, y) => self.chests.get(&(x, y)),
+            Container::Hold(i, j) => self.ship.hold(i, j),
+        }
+    }
+
+    /*
+        Moves a stack from the player's inventory into the open container, as much
+        of it as fits.
+
+        @param slot The player's slot.
+        @return u32 How many items were moved.
+    */
+    pub fn store(&mut self, slot: usize) -> u32 {
+        self.transfer(slot, true)
+    }
+
+    /*
+        Moves a stack from the open container into the player's inventory, as much
+        of it as fits.
+
+        @param slot The container's slot.
+        @return u32 How many items were moved.
+    */
+    pub fn retrieve(&mut self, slot: usize) -> u32 {
+        self.transfer(slot, false)
+    }
+
+    /*
+        Moves a stack between the player and the open container. The ship is
+        reweighed, in case its cargo changed.
+    */
+    fn transfer(&mut self, slot: usize, storing: bool) -> u32 {
+        let moved = {
+            let storage = match self.open_container {
+                Some(Container::Chest(x, y)) => self.chests.get_mut(&(x, y)),
+                Some(Container::Hold(i, j)) => self.ship.hold_mut(i, j),
+                None => None,
+            };
+            let storage = match storage {
+                Some(storage) => storage,
+                None => return 0,
+            };
+            let carried = &mut self.player.inventory;
+            if storing {
+                carried.move_stack(slot, storage)
+            } else {
+                storage.move_stack(slot, carried)
+            }
+        };
+        self.ship.recompute_stats();
+        moved
+    }
+
+    /*
+        Opens the container at hand: the cargo hold the player stands on, or the
+        chest they face.
+
+        @return bool Whether a container was opened.
+    */
+    fn open_storage(&mut self) -> bool {
+        let container = match self.player_location {
+            PlayerLocation::OnShip => match self.ship_tile_under_player() {
+                Some((i, j)) if self.ship.hold_mut(i, j).is_some() => Container::Hold(i, j),
+                _ => return false,
+            },
+            PlayerLocation::InWorld => {
+                let (x, y) = self.facing_tile();
+                if !self.map.is_tile_type(x, y, &TileType::Chest) {
+                    return false;
+                }
+                self.chests
+                    .entry((x, y))
+                    .or_insert_with(|| Inventory::new(CHEST_SLOTS, CHEST_CAPACITY));
+                Container::Chest(x, y)
+            }
+        };
+        self.open_container = Some(container);
+        true
+    }
+
+    /*
+        Whether a tile of some type is within reach of the player, on the ground
+        the player stands on.
+
+        @param station The tile type to look for.
+        @return bool Whether such a tile is close by.
+    */
+    pub fn near_station(&self, station: &TileType) -> bool {
+        let node = self.player_node();
+        let reach = STATION_REACH;
+        (node.x - reach..node.x + reach + 1).any(|x| {
+            (node.y - reach..node.y + reach + 1).any(|y| {
+                let tile = match node.layer {
+                    Layer::Deck => self.ship.tile_at(x, y),
+                    Layer::Ground => self.map.tile(x, y),
+                };
+                tile.is_some_and(|tile| tile.tile_type == *station)
+            })
+        })
+    }
+
+    /*
+        Whether the player can craft a recipe here and now: at its station, if it
+        needs one, and carrying all it takes.
+
+        @param recipe The recipe.
+        @return bool Whether it can be crafted.
+    */
+    pub fn can_craft(&self, recipe: &Recipe) -> bool {
+        let at_station = match recipe.station {
+            Some(ref station) => self.near_station(station),
+            None => true,
+        };
+        at_station && recipe
+            .inputs
+            .iter()
+            .all(|&(ref id, count)| self.player.inventory.count(id) >= count)
+    }
+
+    /*
+        Crafts a recipe, using up its inputs from the player's inventory. Whatever
+        is made that the player has no room for is dropped at their feet.
+
+        @param index The recipe's place in the recipe book.
+        @return bool Whether anything was crafted.
+    */
+    pub fn craft(&mut self, index: usize) -> bool {
+        let recipe = match self.recipes.recipes.get(index) {
+            Some(recipe) if self.can_craft(recipe) => recipe.clone(),
+            _ => return false,
+        };
+        for &(ref id, count) in &recipe.inputs {
+            self.player.inventory.remove(id, count);
+        }
+        let on_ship = self.player_location == PlayerLocation::OnShip;
+        let (x, y) = (self.player.x, self.player.y);
+        for &(ref id, count) in &recipe.outputs {
+            for _ in 0..count {
+                let mut item = self.item_prototypes[id].generate_clone(x, y);
+                item.on_ship = on_ship;
+                if self.player.inventory.can_add(&item) {
+                    self.player.pickup_item(item);
+                } else {
+                    self.items_in_game.push(item);
+                }
+            }
+        }
+        true
+    }
+
+    /*
+        Determines whether the game has been lost.
+
+        @return bool Whether the player has died.
+    */
+    pub fn is_game_over(&self) -> bool {
+        self.player.is_dead()
+    }
+
+    /*
+        Steps the world by one simulation tick, most importantly player position.
+    */
+    pub fn update(&mut self) {
+        if self.updates_since_last_gen > 1000 {
+            self.updates_since_last_gen = 0;
+
+            let mut range_x =
+                distributions::Range::new(-self.view_width / 2.0, self.view_width / 2.0);
+            let mut range_y =
+                distributions::Range::new(-self.view_height / 2.0, self.view_height / 2.0);
+
+            let item_x = self.player.x + range_x.sample(&mut self.rng);
+            let item_y = self.player.y + range_y.sample(&mut self.rng);
+
+            // Only spawn onto walkable island ground, never into open sky.
+            let (tile_x, tile_y) = (tile_coord(item_x), tile_coord(item_y));
+            let on_island = self.map.island_at(tile_x, tile_y).is_some();
+            let passable = self.map
+                .tile(tile_x, tile_y)
+                .is_some_and(|tile| tile.passable);
+            if on_island && passable {
+                self.items_in_game.push(
+                    self.item_prototypes
+                        .get("bisket")
+                        .unwrap()
+                        .generate_clone(item_x, item_y),
+                );
+            }
+        }
+        self.updates_since_last_gen += 1;
+
+        if self.updates_since_last_spawn > CREATURE_SPAWN_INTERVAL {
+            self.updates_since_last_spawn = 0;
+            self.spawn_creature();
+        }
+        self.updates_since_last_spawn += 1;
+
+        if self.updates_since_last_work > CREW_WORK_INTERVAL {
+            self.updates_since_last_work = 0;
+            self.crew_work();
+        }
+        self.updates_since_last_work += 1;
+
+        self.player.update_timers();
+        self.player.update_needs();
+        self.map.load_around(self.player.x, self.player.y);
+        // The ship coasts under drag once its controls are released.
+        self.ship.update_self_velocity();
+        let (ship_dx, ship_dy) = self.move_ship();
+        // Building is only possible while on foot.
+        if self.player.creature_state != CreatureState::Normal {
+            self.build_cursor = None;
+        }
+        // Without a wheel there is nothing left to steer with.
+        if self.player.creature_state == CreatureState::ControllingShip
+            && !self.ship.has_tile(TileType::Wheel)
+        {
+            self.player.change_control_state();
+        }
+        // Left alone, the ship's controls go slack; a helmsman holds its course.
+        if self.player.creature_state != CreatureState::ControllingShip && !self.helm_manned() {
+            self.ship.reset_dir();
+        }
+        match self.player_location {
+            PlayerLocation::OnShip => {
+                self.player.other_vel_x = ship_dx;
+                self.player.other_vel_y = ship_dy;
+                self.player.update_position_other();
+                let x = self.player.x_to_be_location();
+                let y = self.player.y_to_be_location();
+                if self.is_on_ship(x, y) {
+                    self.player.update_position_self();
+                    self.player.update_direction();
+                }
+            }
+            PlayerLocation::InWorld => {
+                let x = self.player.x_to_be_location();
+                let y = self.player.y_to_be_location();
+                if self.can_go_to(x, y) {
+                    self.player.update_position_self();
+                    self.player.update_direction();
+                }
+            }
+        }
+        self.update_gathering();
+        self.update_regrowth();
+        self.update_items(ship_dx, ship_dy);
+        self.update_creatures(ship_dx, ship_dy);
+    }
+
+    /*
+        The tile the player is chopping or mining, if any.
+
+        @return Option<(i32, i32)> The tile's coordinates.
+    */
+    pub fn gathering(&self) -> Option<(i32, i32)> {
+        self.gathering
+    }
+
+    /*
+        Finds the map tile in front of the player.
+
+        @return (i32, i32) The tile's coordinates.
+    */
+    fn facing_tile(&self) -> (i32, i32) {
+        let (dx, dy) = self.player.facing().vector();
+        let x = tile_coord(self.player.x + IMAGE_SIZE_SCALED / 2.0) + dx as i32;
+        let y = tile_coord(self.player.y + IMAGE_SIZE_SCALED / 2.0) + dy as i32;
+        (x, y)
+    }
+
+    /*
+        Starts chopping or mining the tile in front of the player, if it can be harvested.
+
+        @return bool Whether the player started gathering.
+    */
+    fn start_gathering(&mut self) -> bool {
+        if self.player_location != PlayerLocation::InWorld {
+            return false;
+        }
+        let (x, y) = self.facing_tile();
+        let harvestable = self.map
+            .tile(x, y)
+            .is_some_and(|tile| tile.tile_type.harvest().is_some());
+        if harvestable {
+            self.gathering = Some((x, y));
+            self.updates_since_last_gather = 0;
+        }
+        harvestable
+    }
+
+    /*
+        Keeps chopping or mining while the player faces the tile, dropping its
+        resources in its place once it gives way.
+    */
+    fn update_gathering(&mut self) {
+        let (x, y) = match self.gathering {
+            Some(target) => target,
+            None => return,
+        };
+        let harvestable = self.map
+            .tile(x, y)
+            .is_some_and(|tile| tile.tile_type.harvest().is_some());
+        if self.player_location != PlayerLocation::InWorld || self.facing_tile() != (x, y)
+            || !harvestable
+        {
+            self.gathering = None;
+            return;
+        }
+        self.updates_since_last_gather += 1;
+        if self.updates_since_last_gather < GATHER_INTERVAL {
+            return;
+        }
+        self.updates_since_last_gather = 0;
+        if let Some(drops) = self.map.hit_tile(x, y, 1) {
+            let (item_x, item_y) = (x as f64 * IMAGE_SIZE_SCALED, y as f64 * IMAGE_SIZE_SCALED);
+            for id in drops {
+                let item = self.item_prototypes[*id].generate_clone(item_x, item_y);
+                self.items_in_game.push(item);
+            }
+            self.gathering = None;
+        }
+    }
+
+    /*
+        Grows harvested tiles back, once nothing on the ground stands in the way.
+    */
+    fn update_regrowth(&mut self) {
+        let occupied = self.occupied_tiles();
+        self.map.update_regrowth(|x, y| occupied.contains(&(x, y)));
+    }
+
+    /*
+        Finds every map tile the player or a creature ashore overlaps.
+
+        @return Vec<(i32, i32)> The tiles' coordinates.
+    */
+    fn occupied_tiles(&self) -> Vec<(i32, i32)> {
+        let mut occupied = vec![];
+        let player = match self.player_location {
+            PlayerLocation::InWorld => Some(&self.player),
+            PlayerLocation::OnShip => None,
+        };
+        let ashore = self.creatures.iter().filter(|creature| !creature.on_ship);
+        for creature in player.into_iter().chain(ashore) {
+            let (x, y) = (creature.x / IMAGE_SIZE_SCALED, creature.y / IMAGE_SIZE_SCALED);
+            for &i in &[x.floor(), x.ceil()] {
+                for &j in &[y.floor(), y.ceil()] {
+                    occupied.push((i as i32, j as i32));
+                }
+            }
+        }
+        occupied
+    }
+
+    /*
+        Tries to spawn a creature on open island ground around the player.
+        Mostly wildlife, with the odd monster and stranded crew member.
+    */
+    fn spawn_creature(&mut self) {
+        if self.creatures.len() >= MAX_CREATURES {
+            return;
+        }
+        let mut range_x = distributions::Range::new(-self.view_width, self.view_width);
+        let mut range_y = distributions::Range::new(-self.view_height, self.view_height);
+        let x = self.player.x + range_x.sample(&mut self.rng);
+        let y = self.player.y + range_y.sample(&mut self.rng);
+        let kind = match self.rng.gen_range(0, 10) {
+            0 => CreatureKind::Crew,
+            1..=3 => CreatureKind::Monster,
+            _ => CreatureKind::Wildlife,
+        };
+
+        let (tile_x, tile_y) = (tile_coord(x), tile_coord(y));
+        if self.map.island_at(tile_x, tile_y).is_some() && self.can_go_to(x, y) {
+            let mut creature = Creature::with_kind(kind);
+            creature.x = x;
+            creature.y = y;
+            self.creatures.push(creature);
+        }
+    }
+
+    /*
+        Steps every item by one tick. Items on deck ride along with the ship, and
+        thrown items fly until friction stops them or they hit something. Items
+        thrown off the edge of the deck fall away.
+
+        @param ship_dx How far the ship moved this tick horizontally.
+        @param ship_dy How far the ship moved this tick vertically.
+    */
+    fn update_items(&mut self, ship_dx: f64, ship_dy: f64) {
+        let items = mem::take(&mut self.items_in_game);
+        let mut kept = Vec::with_capacity(items.len());
+        for mut item in items {
+            if item.on_ship {
+                item.x += ship_dx;
+                item.y += ship_dy;
+            }
+            if item.is_moving() {
+                let (vx, vy) = item.velocity();
+                let (x, y) = (item.x + vx, item.y + vy);
+                if item.on_ship && !self.is_on_ship(x, y) {
+                    continue; // Overboard.
+                }
+                if item.on_ship || self.can_go_to(x, y) {
+                    item.x = x;
+                    item.y = y;
+                } else {
+                    item.stop();
+                }
+                self.strike_with(&mut item);
+                item.slide();
+            }
+            kept.push(item);
+        }
+        self.items_in_game = kept;
+    }
+
+    /*
+        Checks whether a flying item hits a creature, hurting and knocking back the
+        first it meets. The item drops where it hits. Crew are never hit.
+
+        @param item The flying item.
+    */
+    fn strike_with(&mut self, item: &mut Item) {
+        let reach = IMAGE_SIZE_SCALED * 0.75;
+        let hit = self.creatures.iter().position(|creature| {
+            creature.on_ship == item.on_ship && creature.kind != CreatureKind::Crew
+                && (creature.x - item.x).abs() < reach
+                && (creature.y - item.y).abs() < reach
+        });
+        if let Some(index) = hit {
+            if self.creatures[index].take_damage(item.damage()) {
+                let (x, y) = (self.creatures[index].x, self.creatures[index].y);
+                let (x, y) = self.shove(x, y, item.on_ship, item.velocity());
+                self.creatures[index].x = x;
+                self.creatures[index].y = y;
+                if self.creatures[index].is_dead() {
+                    self.creatures.remove(index);
+                }
+            }
+            item.stop();
+        }
+    }
+
+    /*
+        Gathers what a creature can sense: the player, if on the same ground,
+        and for creatures in the world, the nearest food close by.
+
+        @param creature The creature perceiving.
+        @param player_on_ship Whether the player is aboard the ship.
+        @return Perception What the creature perceives.
+    */
+    fn perceive(&self, creature: &Creature, player_on_ship: bool) -> Perception {
+        let player = if creature.on_ship == player_on_ship && !self.player.is_dead() {
+            Some((self.player.x, self.player.y))
+        } else {
+            None
+        };
+        let food = if creature.on_ship {
+            None
+        } else {
+            let distance = |item: &Item| (item.x - creature.x).hypot(item.y - creature.y);
+            self.items_in_game
+                .iter()
+                .filter(|item| item.item_type == ItemType::Food)
+                .filter(|item| distance(item) < FORAGE_RADIUS)
+                .min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap())
+                .map(|item| (item.x, item.y))
+        };
+        Perception {
+            player,
+            food,
+            waypoint: None,
+        }
+    }
+
+    /*
+        Keeps a creature's route to a goal up to date, searching afresh whenever the
+        goal moves to another tile. Searches share a budget, so a search that cannot
+        finish this tick carries on in the next. Routes may cross the portal, taking
+        the creature aboard or ashore.
+
+        @param creature The creature following a route.
+        @param goal Where the creature is headed.
+        @param budget How many nodes may still be searched this tick.
+        @return Option<(f64, f64)> Where the creature should step next, if anywhere.
+    */
+    fn plan_route(
+        &self,
+        creature: &mut Creature,
+        goal: Node,
+        budget: &mut usize,
+    ) -> Option<(f64, f64)> {
+        let grid = NavGrid::new(&self.map, &self.ship);
+        let centre = IMAGE_SIZE_SCALED / 2.0;
+        let layer = if creature.on_ship { Layer::Deck } else { Layer::Ground };
+        let start = grid.node_at(layer, creature.x + centre, creature.y + centre);
+
+        let brain = &mut creature.brain;
+        if brain.search.as_ref().map(|search| search.goal) != Some(goal) {
+            brain.search = Some(Search::new(start, goal));
+        }
+        if let Some(ref mut search) = brain.search {
+            if search.status == SearchStatus::Searching {
+                *budget -= search.step(&grid, *budget);
+                match search.status {
+                    SearchStatus::Found(ref route) => brain.route = route.clone(),
+                    SearchStatus::NoPath => brain.route.clear(),
+                    SearchStatus::Searching => {}
+                }
+            }
+        }
+        // Step onto each waypoint reached, lining up exactly so the next step clears corners.
+        while let Some(&node) = brain.route.first() {
+            let (x, y) = grid.position(node);
+            // Once at a portal, the next step is straight through it.
+            let crosses = (node.layer == Layer::Deck) != creature.on_ship;
+            let far = (x - creature.x).abs() > creature.speed
+                || (y - creature.y).abs() > creature.speed;
+            if far && !crosses {
+                break;
+            }
+            creature.on_ship = node.layer == Layer::Deck;
+            creature.x = x;
+            creature.y = y;
+            brain.route.remove(0);
+        }
+        brain.route.first().map(|&node| grid.position(node))
+    }
+
+    /*
+        Finds the node the player stands on.
+
+        @return Node The player's node.
+    */
+    fn player_node(&self) -> Node {
+        let grid = NavGrid::new(&self.map, &self.ship);
+        let layer = match self.player_location {
+            PlayerLocation::OnShip => Layer::Deck,
+            PlayerLocation::InWorld => Layer::Ground,
+        };
+        let centre = IMAGE_SIZE_SCALED / 2.0;
+        grid.node_at(layer, self.player.x + centre, self.player.y + centre)
+    }
+
+    /*
+        Finds the first deck tile of some type.
+
+        @param tile_type The type to look for.
+        @return Option<Node> The tile's node on the deck.
+    */
+    fn deck_node(&self, tile_type: TileType) -> Option<Node> {
+        for (i, column) in self.ship.tiles.iter().enumerate() {
+            for (j, tile) in column.iter().enumerate() {
+                if tile.tile_type == tile_type {
+                    return Some(Node::new(Layer::Deck, i as i32, j as i32));
+                }
+            }
+        }
+        None
+    }
+
+    /*
+        Decides where a recruited crew member needs to go. Gatherers head ashore
+        through the portal; everyone else heads aboard, the helmsman to the wheel and
+        repairers to the repair post.
+
+        @param creature The crew member.
+        @return Option<Node> Where they are headed, if anywhere in particular.
+    */
+    fn crew_goal(&self, creature: &Creature) -> Option<Node> {
+        let portal = self.deck_node(TileType::Portal)?;
+        match (creature.station, creature.on_ship) {
+            (Some(Station::Gather), true) => {
+                NavGrid::new(&self.map, &self.ship).portal_link(portal)
+            }
+            (Some(Station::Gather), false) => None,
+            (_, false) => Some(portal),
+            (Some(Station::Helm), true) => self.deck_node(TileType::Wheel),
+            (Some(Station::Repair), true) => self.deck_node(TileType::RepairPost),
+            (_, true) => None,
+        }
+    }
+
+    /*
+        Whether a crew member is standing at the wheel, ready to hold the ship's course.
+
+        @return bool Whether the helm is manned.
+    */
+    fn helm_manned(&self) -> bool {
+        self.creatures.iter().any(|creature| {
+            creature.recruited && creature.on_ship && creature.station == Some(Station::Helm)
+                && creature.brain.state == AiState::Work
+        })
+    }
+
+    /*
+        Recruits the nearest crew member within reach of the player, if any.
+
+        @return bool Whether anyone was recruited.
+    */
+    fn recruit_crew(&mut self) -> bool {
+        let (x, y) = (self.player.x, self.player.y);
+        let distance = |creature: &Creature| (creature.x - x).hypot(creature.y - y);
+        let on_ship = self.player_location == PlayerLocation::OnShip;
+        let recruit = self.creatures
+            .iter_mut()
+            .filter(|creature| creature.kind == CreatureKind::Crew && !creature.recruited)
+            .filter(|creature| creature.on_ship == on_ship)
+            .filter(|creature| distance(creature) <= RECRUIT_REACH)
+            .min_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap());
+        match recruit {
+            Some(creature) => {
+                creature.recruited = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /*
+        Puts stationed crew to work: repairers standing at a repair post mend the most
+        damaged hull tile, using up a log each time, and gatherers ashore chop away at
+        the nearest tree close by.
+    */
+    fn crew_work(&mut self) {
+        let mut repairers = 0;
+        let mut chops = vec![];
+        for creature in &self.creatures {
+            match (creature.station, creature.on_ship) {
+                (Some(Station::Repair), true) => {
+                    let at_post = match self.ship_tile_under(creature.x, creature.y) {
+                        Some((i, j)) => self.ship.tiles[i][j].tile_type == TileType::RepairPost,
+                        None => false,
+                    };
+                    if at_post {
+                        repairers += 1;
+                    }
+                }
+                (Some(Station::Gather), false) => {
+                    // Leave a pile rather than a heap: one log at a time underfoot.
+                    let has_logs = self.items_in_game.iter().any(|item| {
+                        item.id == "logs"
+                            && (item.x - creature.x).abs() < IMAGE_SIZE_SCALED
+                            && (item.y - creature.y).abs() < IMAGE_SIZE_SCALED
+                    });
+                    if let Some(tree) = self.tree_to_chop(creature).filter(|_| !has_logs) {
+                        chops.push((tree, creature.x, creature.y));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for _ in 0..repairers {
+            let damaged = (0..self.ship.tiles.len())
+                .flat_map(|i| (0..self.ship.tiles[i].len()).map(move |j| (i, j)))
+                .filter(|&(i, j)| self.ship.tiles[i][j].is_damaged())
+                .min_by_key(|&(i, j)| self.ship.tiles[i][j].health);
+            match damaged {
+                Some((i, j)) if self.use_log() => self.ship.repair_tile(i, j),
+                _ => break,
+            }
+        }
+        for ((x, y), item_x, item_y) in chops {
+            if let Some(drops) = self.map.hit_tile(x, y, 1) {
+                for id in drops {
+                    let item = self.item_prototypes[*id].generate_clone(item_x, item_y);
+                    self.items_in_game.push(item);
+                }
+            }
+        }
+    }
+
+    /*
+        Picks the tree a gatherer chops at: the most worn within reach, so each tree
+        is felled before the next is started, and the nearest of those.
+
+        @param creature The gatherer.
+        @return Option<(i32, i32)> The tree's tile coordinates, if one is close enough.
+    */
+    fn tree_to_chop(&self, creature: &Creature) -> Option<(i32, i32)> {
+        let x = tile_coord(creature.x + IMAGE_SIZE_SCALED / 2.0);
+        let y = tile_coord(creature.y + IMAGE_SIZE_SCALED / 2.0);
+        let reach = GATHER_REACH;
+        (x - reach..x + reach + 1)
+            .flat_map(|i| (y - reach..y + reach + 1).map(move |j| (i, j)))
+            .filter_map(|(i, j)| match self.map.tile(i, j) {
+                Some(tile) if tile.tile_type == TileType::Tree => Some((i, j, tile.health)),
+                _ => None,
+            })
+            .min_by_key(|&(i, j, health)| (health, (i - x).abs() + (j - y).abs()))
+            .map(|(i, j, _)| (i, j))
+    }
+
+    /*
+        Uses up a log for work on the ship, taking it from the cargo holds before
+        the player's inventory.
+
+        @return bool Whether there was a log to use.
+    */
+    fn use_log(&mut self) -> bool {
+        let hold = self.ship
+            .cargo()
+            .iter()
+            .filter(|&(_, hold)| hold.count("logs") > 0)
+            .map(|(&index, _)| index)
+            .min();
+        if let Some((i, j)) = hold {
+            self.ship.hold_mut(i, j).expect("cargo is kept in holds").remove("logs", 1);
+            self.ship.recompute_stats();
+            return true;
+        }
+        self.player.inventory.remove("logs", 1)
+    }
+
+    /*
+        Steps every creature by one tick. Creatures keep to the ground they are on -
+        the ship's deck or island - and are carried along by the ship when aboard.
+        Creatures left behind in unloaded parts of the world are removed.
+
+        @param ship_dx How far the ship moved this tick horizontally.
+        @param ship_dy How far the ship moved this tick vertically.
+    */
+    fn update_creatures(&mut self, ship_dx: f64, ship_dy: f64) {
+        let mut creatures = mem::take(&mut self.creatures);
+        let player_on_ship = self.player_location == PlayerLocation::OnShip;
+        let mut budget = PATH_BUDGET;
+        for creature in &mut creatures {
+            let mut perception = self.perceive(creature, player_on_ship);
+            // Monsters find their way around obstacles to the player.
+            if creature.kind == CreatureKind::Monster && perception.player.is_some() {
+                let goal = self.player_node();
+                perception.waypoint = self.plan_route(creature, goal, &mut budget);
+            }
+            if creature.recruited {
+                perception.waypoint = match self.crew_goal(creature) {
+                    Some(goal) => self.plan_route(creature, goal, &mut budget),
+                    None => {
+                        creature.brain.route.clear();
+                        creature.brain.search = None;
+                        None
+                    }
+                };
+            }
+            creature.update_timers();
+            if ai::think(creature, &perception, &mut self.rng) && self.player.take_damage(1) {
+                let (x, y) = (self.player.x, self.player.y);
+                let away = (x - creature.x, y - creature.y);
+                let (x, y) = self.shove(x, y, player_on_ship, away);
+                self.player.x = x;
+                self.player.y = y;
+            }
+
+            if creature.on_ship {
+                creature.other_vel_x = ship_dx;
+                creature.other_vel_y = ship_dy;
+                creature.update_position_other();
+            }
+            let x = creature.x_to_be_location();
+            let y = creature.y_to_be_location();
+            let can_move = if creature.on_ship {
+                self.is_on_ship(x, y)
+            } else {
+                self.can_go_to(x, y)
+            };
+            creature.brain.blocked = !can_move;
+            if can_move {
+                creature.update_position_self();
+                creature.update_direction();
+            } else {
+                creature.stop();
+            }
+
+            // Foraging wildlife eats the food it reaches.
+            if creature.brain.state == AiState::Forage {
+                let reach = IMAGE_SIZE_SCALED / 2.0;
+                self.items_in_game.retain(|item| {
+                    let is_food = item.item_type == ItemType::Food;
+                    !(is_food && (item.x - creature.x).abs() < reach
+                        && (item.y - creature.y).abs() < reach)
+                });
+            }
+        }
+        creatures.retain(|creature| {
+            let loaded = self.map
+                .tile(tile_coord(creature.x), tile_coord(creature.y))
+                .is_some();
+            !creature.is_dead() && (creature.on_ship || creature.recruited || loaded)
+        });
+        self.creatures = creatures;
+    }
+
+    /*
+        Moves the ship by its velocity, one axis at a time so that it slides along
+        terrain rather than sticking to it. Blocked axes stop dead and the hull
+        takes damage from hard impacts.
+
+        @return (f64, f64) How far the ship actually moved.
+    */
+    fn move_ship(&mut self) -> (f64, f64) {
+        let (start_x, start_y) = (self.ship.x, self.ship.y);
+        let x = self.ship.x_to_be_location();
+        let y = self.ship.y_to_be_location();
+
+        // A ship already wedged in terrain may move only in ways that don't drive it
+        // any deeper, so it can work itself free.
+        let wedged = self.ship_overlap(start_x, start_y);
+        if wedged > 0.0 {
+            if self.ship_overlap(x, start_y) <= wedged + OVERLAP_TOLERANCE {
+                self.ship.x = x;
+            } else {
+                self.ship.self_vel_x = 0.0;
+            }
+            let wedged = self.ship_overlap(self.ship.x, start_y);
+            if self.ship_overlap(self.ship.x, y) <= wedged + OVERLAP_TOLERANCE {
+                self.ship.y = y;
+            } else {
+                self.ship.self_vel_y = 0.0;
+            }
+            return (self.ship.x - start_x, self.ship.y - start_y);
+        }
+        if self.ship_collisions(x, y).is_empty() {
+            self.ship.update_position();
+            return (self.ship.x - start_x, self.ship.y - start_y);
+        }
+
+        let hits = self.ship_collisions(x, start_y);
+        if hits.is_empty() {
+            self.ship.x = x;
+        } else {
+            let speed = self.ship.self_vel_x;
+            self.ship.impact(speed, &hits);
+            self.ship.self_vel_x = 0.0;
+        }
+
+        let hits = self.ship_collisions(self.ship.x, y);
+        if hits.is_empty() {
+            self.ship.y = y;
+        } else {
+            let speed = self.ship.self_vel_y;
+            self.ship.impact(speed, &hits);
+            self.ship.self_vel_y = 0.0;
+        }
+
+        (self.ship.x - start_x, self.ship.y - start_y)
+    }
+
+    /*
+        Measures how far the ship would be wedged into terrain that blocks it, as the
+        total area its solid (non-Air) tiles would overlap.
+
+        @param ship_x The ship's prospective horizontal position.
+        @param ship_y The ship's prospective vertical position.
+        @return f64 The overlapping area, in square pixels.
+    */
+    fn ship_overlap(&self, ship_x: f64, ship_y: f64) -> f64 {
+        let mut overlap = 0.0;
+        for (i, column) in self.ship.tiles.iter().enumerate() {
+            for (j, tile) in column.iter().enumerate() {
+                if tile.tile_type == TileType::Air {
+                    continue;
+                }
+                let left = ship_x + i as f64 * IMAGE_SIZE_SCALED;
+                let top = ship_y + j as f64 * IMAGE_SIZE_SCALED;
+                for map_x in tile_coord(left)..=tile_coord(left + IMAGE_SIZE_SCALED) {
+                    for map_y in tile_coord(top)..=tile_coord(top + IMAGE_SIZE_SCALED) {
+                        let blocks = self.map
+                            .tile(map_x, map_y)
+                            .is_some_and(|t| t.tile_type.blocks_ship());
+                        if !blocks {
+                            continue;
+                        }
+                        let (map_left, map_top) =
+                            (map_x as f64 * IMAGE_SIZE_SCALED, map_y as f64 * IMAGE_SIZE_SCALED);
+                        let width = (left + IMAGE_SIZE_SCALED).min(map_left + IMAGE_SIZE_SCALED)
+                            - left.max(map_left);
+                        let height = (top + IMAGE_SIZE_SCALED).min(map_top + IMAGE_SIZE_SCALED)
+                            - top.max(map_top);
+                        overlap += width.max(0.0) * height.max(0.0);
+                    }
+                }
+            }
+        }
+        overlap
+    }
+
+    /*
+        Finds the ship tiles that would overlap terrain that blocks the ship.
+        Only the ship's solid (non-Air) tiles count.
+
+        @param ship_x The ship's prospective horizontal position.
+        @param ship_y The ship's prospective vertical position.
+        @return Vec<(usize, usize)> Indices of the ship tiles that would hit something.
+    */
+    fn ship_collisions(&self, ship_x: f64, ship_y: f64) -> Vec<(usize, usize)> {
+        // Shrink each tile slightly so ship edges flush with a wall don't count.
+        let inset = 0.01;
+        let mut hits = vec![];
+        for (i, column) in self.ship.tiles.iter().enumerate() {
+            for (j, tile) in column.iter().enumerate() {
+                if tile.tile_type == TileType::Air {
+                    continue;
+                }
+                let left = ship_x + i as f64 * IMAGE_SIZE_SCALED;
+                let top = ship_y + j as f64 * IMAGE_SIZE_SCALED;
+                let right = left + IMAGE_SIZE_SCALED;
+                let bottom = top + IMAGE_SIZE_SCALED;
+                let hit = (tile_coord(left + inset)..=tile_coord(right - inset)).any(|map_x| {
+                    (tile_coord(top + inset)..=tile_coord(bottom - inset)).any(|map_y| {
+                        self.map
+                            .tile(map_x, map_y)
+                            .is_some_and(|t| t.tile_type.blocks_ship())
+                    })
+                });
+                if hit {
+                    hits.push((i, j));
+                }
+            }
+        }
+        hits
+    }
+
+    /*
+        Repairs the most damaged ship tile under or beside the player, using up
+        the logs they are holding.
+
+        @return bool Whether a repair was made.
+    */
+    fn repair_ship(&mut self) -> bool {
+        if !self.player_has_logs() {
+            return false;
+        }
+        let (i, j) = match self.ship_tile_under_player() {
+            Some(index) => index,
+            None => return false,
+        };
+
+        let mut candidates = vec![(i, j), (i + 1, j), (i, j + 1)];
+        if i > 0 {
+            candidates.push((i - 1, j));
+        }
+        if j > 0 {
+            candidates.push((i, j - 1));
+        }
+        let target = candidates
+            .into_iter()
+            .filter(|&(i, j)| {
+                self.ship
+                    .tiles
+                    .get(i)
+                    .and_then(|column| column.get(j))
+                    .is_some_and(|tile| tile.is_damaged())
+            })
+            .min_by_key(|&(i, j)| self.ship.tiles[i][j].health);
+
+        match target {
+            Some((i, j)) => {
+                self.ship.repair_tile(i, j);
+                self.player.inventory.remove("logs", 1);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /*
+        Checks whether the player is carrying logs, the material for ship work.
+
+        @return bool Whether logs are in the player's inventory.
+    */
+    fn player_has_logs(&self) -> bool {
+        self.player.inventory.count("logs") > 0
+    }
+
+    /*
+        Determines the indices of the ship tile the player is standing on.
+
+        @return Option<(usize, usize)> The tile's indices, if the player is over the ship.
+    */
+    fn ship_tile_under_player(&self) -> Option<(usize, usize)> {
+        self.ship_tile_under(self.player.x, self.player.y)
+    }
+
+    /*
+        Determines the indices of the ship tile beneath a creature's position.
+
+        @param x The creature's x position.
+        @param y The creature's y position.
+        @return Option<(usize, usize)> The tile's indices, if the position is over the ship.
+    */
+    fn ship_tile_under(&self, x: f64, y: f64) -> Option<(usize, usize)> {
+        let x = x + IMAGE_SIZE_SCALED / 2.0;
+        let y = y + IMAGE_SIZE_SCALED / 2.0;
+        let i = ((x - self.ship.x) / IMAGE_SIZE_SCALED).floor();
+        let j = ((y - self.ship.y) / IMAGE_SIZE_SCALED).floor();
+        if i >= 0.0 && j >= 0.0 && i < self.ship.width && j < self.ship.height {
+            Some((i as usize, j as usize))
+        } else {
+            None
+        }
+    }
+
+    /*
+        Determines the tile the player is standing on.
+
+        @return Option<TileType> Some tile.
+    */
+    fn tile_under_player(&self, location: PlayerLocation) -> Option<Tile> {
+        let x = self.player.x + IMAGE_SIZE_SCALED / 2.0;
+        let y = self.player.y + IMAGE_SIZE_SCALED / 2.0;
+        let iss = IMAGE_SIZE_SCALED;
+        match location {
+            PlayerLocation::OnShip => {
+                let is_in_x = x >= self.ship.x && x + iss <= self.ship.x + self.ship.width * iss;
+                let is_in_y = y >= self.ship.y && y + iss <= self.ship.y + self.ship.height * iss;
+                if is_in_x && is_in_y {
+                    return Some(
+                        self.ship.tiles[((x - self.ship.x) / IMAGE_SIZE_SCALED).floor() as usize]
+                            [((y - self.ship.y) / IMAGE_SIZE_SCALED).floor() as usize]
+                            .clone(),
+                    );
+                }
+            }
+            PlayerLocation::InWorld => {
+                return self.map.tile_at_position(x, y).cloned();
+            }
+        }
+        None
+    }
+
+    /*
+        Determines whether some x and y can be stepped on.
+
+        @param x Some x coordinate.
+        @param y Some y coordinate.
+        @return bool Whether the respective tile is passable.
+    */
+    fn can_go_to(&self, x: f64, y: f64) -> bool {
+        let x = x / IMAGE_SIZE_SCALED;
+        let y = y / IMAGE_SIZE_SCALED;
+        // Unloaded chunks are treated as impassable.
+        let passable = |i: f64, j: f64| {
+            self.map
+                .tile(i as i32, j as i32)
+                .is_some_and(|tile| tile.passable)
+        };
+        // Determines where x,y is on the map.
+        passable(x.floor(), y.floor()) && passable(x.floor(), y.ceil())
+            && passable(x.ceil(), y.floor()) && passable(x.ceil(), y.ceil())
+    }
+
+    /*
+        Checks whether a specific x,y position is on the ship.
+        Used for collision detection.
+
+        @param x Some x coordinate.
+        @param y Some y coordinate.
+        @return bool Whether the x,y coordinate is within the ship.
+    */
+    fn is_on_ship(&self, x: f64, y: f64) -> bool {
+        // The ship has already moved this tick.
+        let ship_x = self.ship.x;
+        let ship_y = self.ship.y;
+
+        // Check edges.
+        let is_in_x =
+            x >= ship_x && x + IMAGE_SIZE_SCALED <= ship_x + self.ship.width * IMAGE_SIZE_SCALED;
+        let is_in_y =
+            y >= ship_y && y + IMAGE_SIZE_SCALED <= ship_y + self.ship.height * IMAGE_SIZE_SCALED;
+        if is_in_x && is_in_y {
+            // Check surrounding tiles.
+            let ship_tile_x = (x - ship_x) / IMAGE_SIZE_SCALED;
+            let ship_tile_y = (y - ship_y) / IMAGE_SIZE_SCALED;
+            if self.ship.tiles[ship_tile_x.floor() as usize][ship_tile_y.floor() as usize].passable
+                && self.ship.tiles[ship_tile_x.floor() as usize][ship_tile_y.ceil() as usize]
+                    .passable
+                && self.ship.tiles[ship_tile_x.ceil() as usize][ship_tile_y.floor() as usize]
+                    .passable
+                && self.ship.tiles[ship_tile_x.ceil() as usize][ship_tile_y.ceil() as usize]
+                    .passable
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    /*
+        Changes player position in the world.
+
+        @param state State of the toggled button (e.g. pressed).
+    */
+    fn change_player_location(&mut self) {
+        self.player_location = match self.player_location {
+            PlayerLocation::OnShip => PlayerLocation::InWorld,
+            PlayerLocation::InWorld => PlayerLocation::OnShip,
+        };
+        self.player.creature_state = CreatureState::Normal;
+    }
+
+    /*
+        Handles "pickup" and "drop" for the player.
+
+        @param state The Button State (e.g. pressed).
+    */
+    fn execute_player_hands(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press {
+            let on_ship = self.player_location == PlayerLocation::OnShip;
+            match self.player.drop_item() {
+                Some(mut item) => {
+                    // Dropping one of the items in hand.
+                    item.on_ship = on_ship;
+                    self.items_in_game.push(item);
+                }
+                None => {
+                    let mut place = -1;
+                    for i in 0..self.items_in_game.len() {
+                        // Determines if player is in range of item, and has room for it.
+                        let diff_x = self.items_in_game[i].x - self.player.x;
+                        let diff_y = self.items_in_game[i].y - self.player.y;
+                        if diff_x < IMAGE_SIZE_SCALED && diff_x > -IMAGE_SIZE_SCALED
+                            && diff_y < IMAGE_SIZE_SCALED
+                            && diff_y > -IMAGE_SIZE_SCALED
+                            && self.items_in_game[i].on_ship == on_ship
+                            && self.items_in_game[i].is_pickupable()
+                            && self.player.inventory.can_add(&self.items_in_game[i])
+                        {
+                            place = i as i32;
+                            break;
+                        }
+                    }
+                    if place != -1 {
+                        // Add item to inventory.
+                        let mut item = self.items_in_game.remove(place as usize);
+                        item.stop();
+                        self.player.pickup_item(item);
+                    }
+                }
+            }
+        }
+    }
+
+    /*
+        Throws the player's item the way they are facing.
+
+        @param state The Button State (e.g. pressed).
+    */
+    fn execute_throw(&mut self, state: &ButtonState) {
+        if *state != ButtonState::Press || self.player.creature_state != CreatureState::Normal {
+            return;
+        }
+        if let Some(mut item) = self.player.drop_item() {
+            let (dir_x, dir_y) = self.player.facing().vector();
+            item.on_ship = self.player_location == PlayerLocation::OnShip;
+            item.throw(dir_x, dir_y);
+            self.items_in_game.push(item);
+        }
+    }
+
+    /*
+        Selects a hotbar slot with the number keys, putting its item in hand.
+
+        @param state The Button State (e.g. pressed).
+        @param key The number key pressed.
+    */
+    fn execute_select_slot(&mut self, state: &ButtonState, key: Key) {
+        if *state != ButtonState::Press {
+            return;
+        }
+        let index = match key {
+            Key::D1 => 0,
+            Key::D2 => 1,
+            Key::D3 => 2,
+            Key::D4 => 3,
+            Key::D5 => 4,
+            Key::D6 => 5,
+            Key::D7 => 6,
+            _ => 7,
+        };
+        self.player.inventory.select(index);
+    }
+
+    /*
+        Enters or leaves build mode. Only possible on foot, away from the wheel.
+        The selected tile carries over if it can be built here too.
+
+        @param state The Button State (e.g. pressed).
+    */
+    fn execute_toggle_build(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Press {
+            self.build_cursor = match self.build_cursor {
+                Some(_) => None,
+                None if self.player.creature_state == CreatureState::Normal => Some((0, 1)),
+                None => None,
+            };
+            if !self.build_options().contains(&self.build_tile) {
+                self.build_tile = self.build_options()[0].clone();
+            }
+        }
+    }
+
+    /*
+        Moves the build cursor, keeping it within reach of the player.
+
+        @param state The Button State (e.g. pressed).
+        @param key The arrow key pressed.
+    */
+    fn execute_move_cursor(&mut self, state: &ButtonState, key: Key) {
+        if *state != ButtonState::Press {
+            return;
+        }
+        if let Some((dx, dy)) = self.build_cursor {
+            let (dx, dy) = match key {
+                Key::Up => (dx, dy - 1),
+                Key::Down => (dx, dy + 1),
+                Key::Left => (dx - 1, dy),
+                Key::Right => (dx + 1, dy),
+                _ => (dx, dy),
+            };
+            self.build_cursor = Some((
+                dx.clamp(-BUILD_REACH, BUILD_REACH),
+                dy.clamp(-BUILD_REACH, BUILD_REACH),
+            ));
+        }
+    }
+
+    /*
+        Builds or tears down at the build cursor. Building costs the tile's
+        materials, on deck or ashore, and tearing down gives them back, into the
+        player's inventory if there is room or at their feet otherwise.
+
+        @param state The Button State (e.g. pressed).
+        @param place Whether to place (true) or remove (false) a tile.
+    */
+    fn execute_build(&mut self, state: &ButtonState, place: bool) {
+        if *state != ButtonState::Press {
+            return;
+        }
+        let (i, j) = match self.build_target() {
+            Some(target) => target,
+            None => return,
+        };
+        if place && self.can_build() {
+            for &(id, count) in self.build_tile.build_cost() {
+                self.player.inventory.remove(id, count);
+            }
+            let tile = Tile::new(self.build_tile.clone());
+            match self.player_location {
+                PlayerLocation::OnShip => {
+                    let shift = self.ship.change_tile(i, j, tile);
+                    self.shift_deck_routes(shift);
+                }
+                PlayerLocation::InWorld => self.map.place_structure(i, j, tile),
+            }
+        } else if !place && self.can_unbuild() {
+            let refund = match self.player_location {
+                PlayerLocation::OnShip => {
+                    let refund = self.ship.tiles[i as usize][j as usize].tile_type.build_cost();
+                    let shift = self.ship.change_tile(i, j, Tile::new(TileType::Air));
+                    self.shift_deck_routes(shift);
+                    refund
+                }
+                PlayerLocation::InWorld => {
+                    self.chests.remove(&(i, j));
+                    match self.map.remove_structure(i, j) {
+                        Some(built) => built.build_cost(),
+                        None => &[],
+                    }
+                }
+            };
+            let (x, y) = (self.player.x, self.player.y);
+            for &(id, count) in refund {
+                for _ in 0..count {
+                    let item = self.item_prototypes[id].generate_clone(x, y);
+                    if !self.player.pickup_item(item.clone()) {
+                        self.items_in_game.push(item);
+                    }
+                }
+            }
+        }
+    }
+
+    /*
+        Keeps creatures' routes on course after the ship's grid has grown or shrunk
+        at its left or top, which renumbers every deck tile. Searches under way are
+        dropped, as the deck they were searching has changed.
+
+        @param shift How many columns and rows were added to the left and top.
+    */
+    fn shift_deck_routes(&mut self, shift: (i32, i32)) {
+        for creature in &mut self.creatures {
+            creature.brain.search = None;
+            if shift == (0, 0) {
+                continue;
+            }
+            for node in &mut creature.brain.route {
+                if node.layer == Layer::Deck {
+                    node.x += shift.0;
+                    node.y += shift.1;
+                }
+            }
+        }
+    }
+
+    /*
+        Checks whether the player carries the materials for something.
+
+        @param cost The ids and counts of the items needed.
+        @return bool Whether there is enough of everything.
+    */
+    fn can_afford(&self, cost: &[(&str, u32)]) -> bool {
+        cost.iter().all(|&(id, count)| self.player.inventory.count(id) >= count)
+    }
+
+    /*
+        Moves the player / ship, depending on player control state.
+        Calls respective ship / player functionality to handle.
+
+        @param state Button State (e.g. pressed or released).
+        @param key Some key that has been pressed.
+    */
+    fn execute_move(&mut self, state: &ButtonState, key: &Option<Key>) {
+        match self.player.creature_state {
+            CreatureState::Normal => {
+                self.player.handle_input(&state, &key);
+                self.player.update_self_velocity();
+            }
+            CreatureState::ControllingShip => {
+                self.ship.handle_input(&state, &key);
+            }
+        }
+    }
+
+    /*
+        Starts gathering from the tile in front of the player, or if there is
+        nothing there to harvest, uses the player's item.
+    */
+    fn gather_or_use_item(&mut self) {
+        if !self.start_gathering() {
+            self.use_item();
+        }
+    }
+
+    /*
+        Uses the player's item: weapons are swung, anything else is used up as usual.
+    */
+    fn use_item(&mut self) {
+        let swing = match self.player.inventory.held() {
+            Some(item) if item.effect() == UseEffect::Swing => Some(item.damage()),
+            _ => None,
+        };
+        match swing {
+            Some(damage) => self.swing_sword(damage),
+            None => self.player.use_item(),
+        }
+    }
+
+    /*
+        Swings the player's sword, hitting every creature within reach in an arc
+        ahead of the player. Those hit are knocked back, and the slain removed.
+        Crew are never hit.
+
+        @param damage The damage dealt to each creature hit.
+    */
+    fn swing_sword(&mut self, damage: i32) {
+        if self.player.stamina < SWING_STAMINA || !self.player.start_attack() {
+            return;
+        }
+        self.player.exert(SWING_STAMINA);
+        let (facing_x, facing_y) = self.player.facing().vector();
+        let on_ship = self.player_location == PlayerLocation::OnShip;
+        let mut creatures = mem::take(&mut self.creatures);
+        for creature in &mut creatures {
+            if creature.on_ship != on_ship || creature.kind == CreatureKind::Crew {
+                continue;
+            }
+            let (dx, dy) = (creature.x - self.player.x, creature.y - self.player.y);
+            let distance = dx.hypot(dy);
+            let in_arc = distance == 0.0 || (dx * facing_x + dy * facing_y) / distance >= SWORD_ARC;
+            if distance <= SWORD_REACH && in_arc && creature.take_damage(damage) {
+                let (x, y) = self.shove(creature.x, creature.y, on_ship, (dx, dy));
+                creature.x = x;
+                creature.y = y;
+            }
+        }
+        creatures.retain(|creature| !creature.is_dead());
+        self.creatures = creatures;
+    }
+
+    /*
+        Knocks something back, stopping short of anything in the way.
+
+        @param x The x position being knocked back.
+        @param y The y position being knocked back.
+        @param on_ship Whether the position is on the ship's deck.
+        @param away The direction to knock it in; no particular length.
+        @return (f64, f64) Where it ends up.
+    */
+    fn shove(&self, x: f64, y: f64, on_ship: bool, away: (f64, f64)) -> (f64, f64) {
+        let length = away.0.hypot(away.1);
+        if length == 0.0 {
+            return (x, y);
+        }
+        let steps = 8;
+        let step_x = away.0 / length * KNOCKBACK / steps as f64;
+        let step_y = away.1 / length * KNOCKBACK / steps as f64;
+        let (mut x, mut y) = (x, y);
+        for _ in 0..steps {
+            let (next_x, next_y) = (x + step_x, y + step_y);
+            let clear = if on_ship {
+                self.is_on_ship(next_x, next_y)
+            } else {
+                self.can_go_to(next_x, next_y)
+            };
+            if !clear {
+                break;
+            }
+            x = next_x;
+            y = next_y;
+        }
+        (x, y)
+    }
+
+    /*
+        Handles player general "action" button, with varying results (a sort of catch all).
+
+        @param state The Button State (e.g. pressed).
+    */
+    fn execute_action(&mut self, state: &ButtonState) {
+        if *state == ButtonState::Release {
+            self.gathering = None;
+        }
+        if *state == ButtonState::Press {
+            if self.player_location == PlayerLocation::OnShip && self.repair_ship() {
+                return;
+            }
+            if self.recruit_crew() || self.open_storage() {
+                return;
+            }
+            let current_location = self.player_location.clone();
+            match self.tile_under_player(current_location) {
+                Some(t) => match t.tile_type {
+                    TileType::Portal => match self.player_location {
+                        PlayerLocation::OnShip => {
+                            if let Some(tile) = self.tile_under_player(PlayerLocation::InWorld)
+                            {
+                                if tile.passable {
+                                    // Anchor the ship so the portal stays beneath it.
+                                    self.ship.stop();
+                                    self.ship.reset_dir();
+                                    self.change_player_location();
+                                    let x = (self.player.x + IMAGE_SIZE_SCALED / 2.0)
+                                        / IMAGE_SIZE_SCALED;
+                                    let y = (self.player.y + IMAGE_SIZE_SCALED / 2.0)
+                                        / IMAGE_SIZE_SCALED;
+                                    self.player.x = x.floor() * IMAGE_SIZE_SCALED;
+                                    self.player.y = y.floor() * IMAGE_SIZE_SCALED;
+
+                                    let x = tile_coord(self.player.x + IMAGE_SIZE_SCALED / 2.0);
+                                    let y = tile_coord(self.player.y + IMAGE_SIZE_SCALED / 2.0);
+                                    let temp = self.map
+                                        .tile(x, y)
+                                        .cloned()
+                                        .expect("portal tile unloaded");
+
+                                    self.map.under_portal = temp;
+                                    self.map.set_tile(x, y, Tile::new(TileType::Portal));
+                                }
+                            }
+                        }
+                        PlayerLocation::InWorld => {
+                            if let Some(tile) = self.tile_under_player(PlayerLocation::OnShip) {
+                                // Boarding needs the ship's own portal intact.
+                                if tile.passable && self.ship.has_tile(TileType::Portal) {
+                                    self.change_player_location();
+                                    let x = (self.player.x + IMAGE_SIZE_SCALED / 2.0)
+                                        / IMAGE_SIZE_SCALED;
+                                    let y = (self.player.y + IMAGE_SIZE_SCALED / 2.0)
+                                        / IMAGE_SIZE_SCALED;
+                                    self.player.x = x.floor() * IMAGE_SIZE_SCALED;
+                                    self.player.y = y.floor() * IMAGE_SIZE_SCALED;
+
+                                    let x = tile_coord(self.player.x + IMAGE_SIZE_SCALED / 2.0);
+                                    let y = tile_coord(self.player.y + IMAGE_SIZE_SCALED / 2.0);
+
+                                    let under_portal = self.map.under_portal.clone();
+                                    self.map.set_tile(x, y, under_portal);
+                                    self.map.under_portal = Tile::new(TileType::Air);
+                                }
+                            }
+                        }
+                    },
+
+                    TileType::Wheel => self.player.change_control_state(),
+                    _ => self.gather_or_use_item(),
+                },
+                _ => self.gather_or_use_item(),
+            }
+        }
+    }
+}